@@ -73,9 +73,14 @@ fn main() -> ! {
         #[cfg(feature = "lux_as_f32")]
         {
             let lux = veml7700_device.read_lux().unwrap();
-            writeln!(tx, "White: {}, Lux: {:2}\r", white, lux).ok();
+            writeln!(tx, "White: {}, Lux: {:2}\r", white, lux.lux()).ok();
         }
-        #[cfg(not(feature = "lux_as_f32"))]
+        #[cfg(all(feature = "lux_as_u32", not(feature = "lux_as_f32")))]
+        {
+            let millilux = veml7700_device.read_millilux().unwrap();
+            writeln!(tx, "White: {}, Lux: {}.{:03}\r", white, millilux / 1000, millilux % 1000).ok();
+        }
+        #[cfg(not(any(feature = "lux_as_f32", feature = "lux_as_u32")))]
         {
             let raw = veml7700_device.read_raw().unwrap();
             writeln!(tx, "White: {}, Raw: {:#06x}", white, raw).ok();
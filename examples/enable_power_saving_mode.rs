@@ -75,7 +75,7 @@ fn main() -> ! {
         #[cfg(feature = "lux_as_f32")]
         {
             let lux = veml7700_device.read_lux().unwrap();
-            writeln!(tx, "White: {}, Lux: {:2}\r", white, lux).ok();
+            writeln!(tx, "White: {}, Lux: {:2}\r", white, lux.lux()).ok();
         }
         #[cfg(not(feature = "lux_as_f32"))]
         {
@@ -16,6 +16,10 @@ use stm32f4xx_hal::{
 
 use core::fmt::Write;
 
+#[cfg(not(feature = "lux_as_f32"))]
+use veml7700::calculate_raw_threshold_value_millilux;
+#[cfg(feature = "lux_as_f32")]
+use veml7700::Lux;
 use veml7700::{FaultCount, Gain, IntegrationTime, Veml7700};
 
 #[entry]
@@ -70,13 +74,21 @@ fn main() -> ! {
     // this will compensate the value automatically before setting it
     #[cfg(feature = "lux_as_f32")]
     {
-        veml7700_device.set_high_threshold_lux(10000.0).unwrap();
-        veml7700_device.set_low_threshold_lux(100.0).unwrap();
+        veml7700_device.set_high_threshold_lux(Lux(10000.0)).unwrap();
+        veml7700_device.set_low_threshold_lux(Lux(100.0)).unwrap();
     }
+    // without floating point, the values can be calculated at compile time
     #[cfg(not(feature = "lux_as_f32"))]
     {
-        veml7700_device.set_high_threshold_raw(0xffff).unwrap(); // TODO: precalculate value
-        veml7700_device.set_low_threshold_raw(0x0000).unwrap(); // TODO: precalculate value
+        const HIGH_THRESHOLD: u16 = calculate_raw_threshold_value_millilux(
+            IntegrationTime::_200ms,
+            Gain::OneQuarter,
+            10_000_000,
+        );
+        const LOW_THRESHOLD: u16 =
+            calculate_raw_threshold_value_millilux(IntegrationTime::_200ms, Gain::OneQuarter, 100_000);
+        veml7700_device.set_high_threshold_raw(HIGH_THRESHOLD).unwrap();
+        veml7700_device.set_low_threshold_raw(LOW_THRESHOLD).unwrap();
     }
     veml7700_device.set_fault_count(FaultCount::Four).unwrap();
     veml7700_device.enable_interrupts().unwrap();
@@ -90,7 +102,7 @@ fn main() -> ! {
         #[cfg(feature = "lux_as_f32")]
         {
             let lux = veml7700_device.read_lux().unwrap();
-            writeln!(tx, "White: {}, Lux: {:2}\r", white, lux).ok();
+            writeln!(tx, "White: {}, Lux: {:2}\r", white, lux.lux()).ok();
         }
         #[cfg(not(feature = "lux_as_f32"))]
         {
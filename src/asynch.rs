@@ -0,0 +1,74 @@
+//! Async driver using the [`embedded_hal_async`] traits.
+
+use crate::{Error, InterruptEvent};
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::{
+    delay::DelayNs,
+    digital::Wait,
+    i2c::{I2c, SevenBitAddress},
+};
+use maybe_async::must_be_async as maybe_async;
+
+// The drivers are compiled once for each mode from the same source.
+#[allow(clippy::duplicate_mod)]
+#[path = "driver.rs"]
+mod driver;
+#[allow(clippy::duplicate_mod)]
+#[path = "interface.rs"]
+mod interface;
+#[allow(clippy::duplicate_mod)]
+#[path = "typestate.rs"]
+pub mod typestate;
+#[allow(clippy::duplicate_mod)]
+#[path = "veml3235_driver.rs"]
+mod veml3235_driver;
+#[allow(clippy::duplicate_mod)]
+#[path = "veml6035_driver.rs"]
+mod veml6035_driver;
+
+pub use self::driver::{Veml6030, Veml7700};
+pub use self::veml3235_driver::Veml3235;
+pub use self::veml6035_driver::Veml6035;
+
+impl<I2C> Veml7700<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Wait for the INT output of the device to be asserted, then read the
+    /// interrupt status and the current measurement.
+    ///
+    /// `pin` is connected to the INT output, which is active low. Waiting for
+    /// a low level rather than a falling edge also returns immediately for an
+    /// interrupt that was asserted before the call. Reading the interrupt
+    /// status releases the INT output again.
+    pub async fn wait_for_interrupt<P: Wait>(
+        &mut self,
+        pin: &mut P,
+    ) -> Result<InterruptEvent, Error<I2C::Error>> {
+        pin.wait_for_low().await.map_err(|e| Error::Pin(e.kind()))?;
+        let status = self.read_interrupt_status().await?;
+        let measurement = self.read_measurement().await?;
+        Ok(InterruptEvent {
+            status,
+            measurement,
+        })
+    }
+}
+
+impl<I2C> typestate::Veml7700<I2C, typestate::Active>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Wait for the INT output of the device to be asserted, then read the
+    /// interrupt status and the current measurement.
+    ///
+    /// See `Veml7700::wait_for_interrupt()`.
+    pub async fn wait_for_interrupt<P: Wait>(
+        &mut self,
+        pin: &mut P,
+    ) -> Result<InterruptEvent, Error<I2C::Error>> {
+        self.driver.wait_for_interrupt(pin).await
+    }
+}
@@ -0,0 +1,87 @@
+//! Blocking driver using the [`embedded_hal`] traits.
+
+use crate::{Error, InterruptEvent};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{Error as _, InputPin},
+    i2c::{I2c, SevenBitAddress},
+};
+use maybe_async::must_be_sync as maybe_async;
+
+// The drivers are compiled once for each mode from the same source.
+#[allow(clippy::duplicate_mod)]
+#[path = "driver.rs"]
+mod driver;
+#[allow(clippy::duplicate_mod)]
+#[path = "interface.rs"]
+mod interface;
+#[allow(clippy::duplicate_mod)]
+#[path = "typestate.rs"]
+pub mod typestate;
+#[allow(clippy::duplicate_mod)]
+#[path = "veml3235_driver.rs"]
+mod veml3235_driver;
+#[allow(clippy::duplicate_mod)]
+#[path = "veml6035_driver.rs"]
+mod veml6035_driver;
+
+pub use self::driver::{Veml6030, Veml7700};
+pub use self::veml3235_driver::Veml3235;
+pub use self::veml6035_driver::Veml6035;
+
+/// Interval at which `Veml7700::wait_for_interrupt()` polls the INT pin.
+const INTERRUPT_POLL_INTERVAL_US: u32 = 1000;
+
+impl<I2C> Veml7700<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Wait for the INT output of the device to be asserted, then read the
+    /// interrupt status and the current measurement.
+    ///
+    /// `pin` is connected to the INT output, which is active low. It is
+    /// polled every millisecond and `Error::Timeout` is returned if it is
+    /// not asserted within `timeout_us` microseconds. Reading the interrupt
+    /// status releases the INT output again.
+    pub fn wait_for_interrupt<P: InputPin, D: DelayNs>(
+        &mut self,
+        pin: &mut P,
+        delay: &mut D,
+        timeout_us: u32,
+    ) -> Result<InterruptEvent, Error<I2C::Error>> {
+        let mut waited_us = 0;
+        while !pin.is_low().map_err(|e| Error::Pin(e.kind()))? {
+            if waited_us >= timeout_us {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(INTERRUPT_POLL_INTERVAL_US);
+            waited_us = waited_us.saturating_add(INTERRUPT_POLL_INTERVAL_US);
+        }
+        let status = self.read_interrupt_status()?;
+        let measurement = self.read_measurement()?;
+        Ok(InterruptEvent {
+            status,
+            measurement,
+        })
+    }
+}
+
+impl<I2C> typestate::Veml7700<I2C, typestate::Active>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Wait for the INT output of the device to be asserted, then read the
+    /// interrupt status and the current measurement.
+    ///
+    /// See `Veml7700::wait_for_interrupt()`.
+    pub fn wait_for_interrupt<P: InputPin, D: DelayNs>(
+        &mut self,
+        pin: &mut P,
+        delay: &mut D,
+        timeout_us: u32,
+    ) -> Result<InterruptEvent, Error<I2C::Error>> {
+        self.driver.wait_for_interrupt(pin, delay, timeout_us)
+    }
+}
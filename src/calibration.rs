@@ -0,0 +1,166 @@
+//! Calibration against a reference lux meter.
+//!
+//! Take measurements of the same light with the sensor behind its window and
+//! with a reference meter, at two or more light levels. [`fit()`] calculates
+//! the scale factor and offset mapping the sensor values to the reference
+//! values by least squares, together with the quality of the fit:
+//!
+//! ```
+//! use veml7700::calibration::{fit, Sample};
+//! use veml7700::{CorrectionModel, Gain, IntegrationTime, Lux};
+//!
+//! let samples = [
+//!     Sample {
+//!         raw: 1000,
+//!         gain: Gain::One,
+//!         integration_time: IntegrationTime::_100ms,
+//!         reference_lux: Lux(150.0),
+//!     },
+//!     Sample {
+//!         raw: 10000,
+//!         gain: Gain::OneEighth,
+//!         integration_time: IntegrationTime::_100ms,
+//!         reference_lux: Lux(12000.0),
+//!     },
+//! ];
+//! let fit = fit(&samples, CorrectionModel::LowGain).unwrap();
+//! let bytes = fit.calibration.to_bytes();
+//! // Store `bytes` in the EEPROM of the unit.
+//! ```
+//!
+//! The calibration is then passed to `set_calibration()` of the driver,
+//! which must use the same correction model.
+
+use crate::device_impl::raw_to_lux;
+use crate::{Calibration, CorrectionModel, Gain, IntegrationTime, Lux, Measurement};
+
+/// ALS measurement paired with the value of a reference lux meter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Raw ALS counts.
+    pub raw: u16,
+    /// Gain used for the measurement.
+    pub gain: Gain,
+    /// Integration time used for the measurement.
+    pub integration_time: IntegrationTime,
+    /// Illuminance measured by the reference meter.
+    pub reference_lux: Lux,
+}
+
+impl Sample {
+    /// Pair a measurement read from the driver with a reference value.
+    pub fn from_measurement(measurement: &Measurement, reference_lux: Lux) -> Self {
+        Sample {
+            raw: measurement.raw,
+            gain: measurement.gain,
+            integration_time: measurement.integration_time,
+            reference_lux,
+        }
+    }
+}
+
+/// Calibration fitted to samples, with the quality of the fit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    /// Calibration mapping the sensor values to the reference values.
+    pub calibration: Calibration,
+    /// Coefficient of determination. 1.0 is a perfect fit, which two samples
+    /// always give.
+    pub r_squared: f32,
+    /// Largest difference between a calibrated sensor value and its
+    /// reference value, in lux.
+    pub max_error_lux: f32,
+}
+
+/// Fit a scale factor and an offset mapping the sensor values to the
+/// reference values by least squares.
+///
+/// The raw values are converted to lux with `model`, which must be the
+/// correction model the driver uses. The white channel gets the same scale
+/// factor.
+///
+/// Returns `None` if `model` is not valid, there are fewer than two different
+/// sensor values or the resulting calibration is not valid, for example
+/// because the reference values decrease with increasing sensor values.
+pub fn fit(samples: &[Sample], model: CorrectionModel) -> Option<Fit> {
+    if !model.is_valid() {
+        return None;
+    }
+    let lux = |sample: &Sample| {
+        f64::from(raw_to_lux(
+            model,
+            sample.integration_time,
+            sample.gain,
+            sample.raw,
+        ))
+    };
+    // Sums are accumulated in f64 to avoid cancellation in the variances.
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(lux).sum::<f64>() / n;
+    let mean_y = samples
+        .iter()
+        .map(|s| f64::from(s.reference_lux.0))
+        .sum::<f64>()
+        / n;
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for sample in samples {
+        let dx = lux(sample) - mean_x;
+        let dy = f64::from(sample.reference_lux.0) - mean_y;
+        sxx += dx * dx;
+        sxy += dx * dy;
+        syy += dy * dy;
+    }
+    // This also rejects fewer than two samples.
+    if sxx <= 0.0 {
+        return None;
+    }
+    let scale = sxy / sxx;
+    let offset = mean_y - scale * mean_x;
+    let calibration = Calibration::new(scale as f32).offset(offset as f32);
+    if !calibration.is_valid() {
+        return None;
+    }
+    let mut residual = 0.0;
+    let mut max_error: f64 = 0.0;
+    for sample in samples {
+        let error = scale * lux(sample) + offset - f64::from(sample.reference_lux.0);
+        residual += error * error;
+        max_error = max_error.max(if error < 0.0 { -error } else { error });
+    }
+    let r_squared = if syy > 0.0 { 1.0 - residual / syy } else { 1.0 };
+    Some(Fit {
+        calibration,
+        r_squared: r_squared as f32,
+        max_error_lux: max_error as f32,
+    })
+}
+
+impl Calibration {
+    /// Size of the calibration as returned by `to_bytes()`.
+    pub const SERIALIZED_LEN: usize = 12;
+
+    /// Serialize the calibration: the scale factor, the offset and the
+    /// white scale factor, or 0 if not set, as little-endian `f32`.
+    pub fn to_bytes(&self) -> [u8; Self::SERIALIZED_LEN] {
+        let mut bytes = [0; Self::SERIALIZED_LEN];
+        bytes[0..4].copy_from_slice(&self.scale.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.white_scale.unwrap_or(0.0).to_le_bytes());
+        bytes
+    }
+
+    /// Deserialize a calibration serialized with `to_bytes()`.
+    ///
+    /// Returns `None` if the values are not a valid calibration, for example
+    /// for erased EEPROM content.
+    pub fn from_bytes(bytes: &[u8; Self::SERIALIZED_LEN]) -> Option<Self> {
+        let value =
+            |i: usize| f32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let mut calibration = Calibration::new(value(0)).offset(value(4));
+        let white_scale = value(8);
+        if white_scale != 0.0 {
+            calibration = calibration.white_scale(white_scale);
+        }
+        calibration.is_valid().then_some(calibration)
+    }
+}
@@ -1,23 +1,44 @@
-use crate::{Gain, IntegrationTime};
-
-#[cfg(feature = "lux_as_f32")]
-use micromath::F32Ext;
+use crate::{CorrectionModel, Gain, IntegrationTime, Lux};
 
 /// Calculate raw value for threshold applying compensation if necessary.
 ///
 /// For values higher than 1000 lx and 1/4 or 1/8 gain, the inverse of the
-/// compensation formula is applied. This involves quite some math so it
-/// may be interesting to calculate the threshold values ahead of time.
-pub fn calculate_raw_threshold_value(it: IntegrationTime, gain: Gain, lux: f32) -> u16 {
-    let factor = get_lux_raw_conversion_factor(it, gain);
-    if (gain == Gain::OneQuarter || gain == Gain::OneEighth) && lux > 1000.0 {
-        let lux = inverse_high_lux_correction(lux);
-        (lux / factor) as u16
+/// compensation formula is applied, which takes a few iterations. It may
+/// be interesting to calculate the threshold values ahead of time, for
+/// example with the `const fn` `calculate_raw_threshold_value_millilux()`.
+///
+/// This uses the default `CorrectionModel::LowGain`. See the driver method of
+/// the same name for the correction model selected on the driver.
+///
+/// Values outside of the range of the ALS register saturate to 0 or
+/// `u16::MAX`. The driver methods setting thresholds in lux return
+/// `Error::InvalidThreshold` for these instead.
+pub fn calculate_raw_threshold_value(it: IntegrationTime, gain: Gain, lux: Lux) -> u16 {
+    raw_threshold_value(CorrectionModel::LowGain, it, gain, lux.0) as u16
+}
+
+/// Like `calculate_raw_threshold_value()` but with the given correction
+/// model and returning `None` for values that cannot be represented in the
+/// ALS register.
+pub(crate) fn checked_raw_threshold_value(
+    model: CorrectionModel,
+    it: IntegrationTime,
+    gain: Gain,
+    lux: f32,
+) -> Option<u16> {
+    let raw = raw_threshold_value(model, it, gain, lux);
+    // This also rejects NaN.
+    if (0.0..65536.0).contains(&raw) {
+        Some(raw as u16)
     } else {
-        (lux / factor) as u16
+        None
     }
 }
 
+fn raw_threshold_value(model: CorrectionModel, it: IntegrationTime, gain: Gain, lux: f32) -> f32 {
+    inverse_correct_lux(model, gain, lux) / get_lux_raw_conversion_factor(it, gain)
+}
+
 pub(crate) fn get_lux_raw_conversion_factor(it: IntegrationTime, gain: Gain) -> f32 {
     let gain_factor = match gain {
         Gain::Two => 1.0,
@@ -36,112 +57,182 @@ pub(crate) fn get_lux_raw_conversion_factor(it: IntegrationTime, gain: Gain) ->
     gain_factor * it_factor
 }
 
-const C0: f32 = 1.0023;
-const C1: f32 = 8.1488e-05;
-const C2: f32 = -9.3924e-09;
-const C3: f32 = 6.0135e-13;
+/// Coefficients of `lux`, `lux^2`, `lux^3` and `lux^4` of the compensation
+/// formula of the VEML7700 application note.
+const COEFFICIENTS: [f32; 4] = [1.0023, 8.1488e-05, -9.3924e-09, 6.0135e-13];
+
+/// Apply the non-linearity correction of `model` to an uncorrected lux value.
+pub(crate) fn correct_lux(model: CorrectionModel, gain: Gain, lux: f32) -> f32 {
+    match model {
+        CorrectionModel::None => lux,
+        CorrectionModel::LowGain => {
+            if (gain == Gain::OneQuarter || gain == Gain::OneEighth) && lux > 1000.0 {
+                polynomial(&COEFFICIENTS, lux)
+            } else {
+                lux
+            }
+        }
+        CorrectionModel::AllSettings => polynomial(&COEFFICIENTS, lux),
+        CorrectionModel::Custom(coefficients) => polynomial(&coefficients, lux),
+    }
+}
+
+/// Inverse of `correct_lux()`.
+fn inverse_correct_lux(model: CorrectionModel, gain: Gain, lux: f32) -> f32 {
+    match model {
+        CorrectionModel::None => lux,
+        CorrectionModel::LowGain => {
+            if (gain == Gain::OneQuarter || gain == Gain::OneEighth) && lux > 1000.0 {
+                inverse_polynomial(&COEFFICIENTS, lux)
+            } else {
+                lux
+            }
+        }
+        CorrectionModel::AllSettings => inverse_polynomial(&COEFFICIENTS, lux),
+        CorrectionModel::Custom(coefficients) => inverse_polynomial(&coefficients, lux),
+    }
+}
+
+fn polynomial(c: &[f32; 4], x: f32) -> f32 {
+    x * (c[0] + x * (c[1] + x * (c[2] + x * c[3])))
+}
+
+fn polynomial_derivative(c: &[f32; 4], x: f32) -> f32 {
+    c[0] + x * (2.0 * c[1] + x * (3.0 * c[2] + x * (4.0 * c[3])))
+}
+
+/// Largest uncorrected lux value of the sensor, for a raw value of 65535 at
+/// 25 ms and 1/8 gain.
+const MAX_UNCORRECTED_LUX: f32 = 65535.0 * 1.8432;
+
+/// Whether the polynomial is strictly increasing over the uncorrected range
+/// of the sensor, so that every corrected value has a single inverse.
+///
+/// The derivative is smallest at either end of the range or where the second
+/// derivative is zero. The second derivative is a quadratic and monotonic on
+/// either side of its vertex, so its zeros are found by bisection.
+pub(crate) fn is_increasing(c: &[f32; 4]) -> bool {
+    let second_derivative = |x: f32| 2.0 * c[1] + x * (6.0 * c[2] + x * (12.0 * c[3]));
+    // Not finite for a cubic term of zero, which leaves a single interval.
+    let vertex = -c[2] / (4.0 * c[3]);
+    let split = if (0.0..MAX_UNCORRECTED_LUX).contains(&vertex) {
+        vertex
+    } else {
+        MAX_UNCORRECTED_LUX
+    };
+    let first_zero = find_zero(second_derivative, 0.0, split);
+    let second_zero = find_zero(second_derivative, split, MAX_UNCORRECTED_LUX);
+    [
+        Some(0.0),
+        Some(MAX_UNCORRECTED_LUX),
+        first_zero,
+        second_zero,
+    ]
+    .into_iter()
+    .flatten()
+    .all(|x| polynomial_derivative(c, x) > 0.0)
+}
+
+/// Zero of a monotonic function between `low` and `high` if it changes sign
+/// in between.
+fn find_zero(f: impl Fn(f32) -> f32, mut low: f32, mut high: f32) -> Option<f32> {
+    let low_is_negative = f(low) < 0.0;
+    if low_is_negative == (f(high) < 0.0) {
+        return None;
+    }
+    // Enough for the full precision of an `f32` over the range of the sensor.
+    for _ in 0..48 {
+        let middle = (low + high) * 0.5;
+        if (f(middle) < 0.0) == low_is_negative {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(low)
+}
 
-pub(crate) fn correct_high_lux(lux: f32) -> f32 {
-    lux.powf(4.0) * C3 + lux.powf(3.0) * C2 + lux * lux * C1 + lux * C0
+const INVERSE_MAX_ITERATIONS: usize = 12;
+
+/// Inverse of `polynomial()`, solved with Newton's method.
+///
+/// The compensation formula of the application note is increasing and convex
+/// for positive values and above its linear term, so `y / c[0]` lies above
+/// the root. Where the quartic term dominates, Newton's method only shrinks
+/// such a start value by a quarter per step, so it is first halved as long as
+/// it stays above the root. The iteration then converges monotonically from
+/// above without overshooting.
+///
+/// Over the whole output range of the sensor, up to 112.8 Mlx for a raw value
+/// of 65535 at 25 ms and 1/8 gain, this takes at most 9 halvings and 7
+/// iterations and the result is within 0.14 ppm of the exact root.
+fn inverse_polynomial(c: &[f32; 4], y: f32) -> f32 {
+    let mut x = y / c[0];
+    while x > 0.0 && polynomial(c, x * 0.5) > y {
+        x *= 0.5;
+    }
+    for _ in 0..INVERSE_MAX_ITERATIONS {
+        let step = (polynomial(c, x) - y) / polynomial_derivative(c, x);
+        x -= step;
+        let tolerance = x * 1e-6;
+        if (-tolerance..=tolerance).contains(&step) {
+            break;
+        }
+    }
+    x
 }
 
-fn inverse_high_lux_correction(lux: f32) -> f32 {
-    // Inverse of the polynomial used to correct for lux > 1000.
-    // `y = 6.0135e-13*(x^4) - 9.3924e-9*(x^3) + 8.1488e-5*(x^2) + 1.0023*x`.
-    // This runs into underflow/overflow issues if trying to solve it directly.
-    // However, it can be solved for unknown coefficients and then
-    // we put in the values.
-    -C2 / (4.0 * C3)
-        - (C2.powf(2.0) / (4.0 * C3.powf(2.0)) - (2.0 * C1) / (3.0 * C3)
-            + (2.0_f32.powf(1.0 / 3.0) * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux))
-                / (3.0
-                    * C3
-                    * (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                        - 27.0 * C2.powf(2.0) * lux
-                        + 72.0 * C3 * C1 * lux
-                        + (-4.0 * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux).powf(3.0)
-                            + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0
-                                + 27.0 * C3 * C0.powf(2.0)
-                                - 27.0 * C2.powf(2.0) * lux
-                                + 72.0 * C3 * C1 * lux)
-                                .powf(2.0))
-                        .sqrt())
-                    .powf(1.0 / 3.0))
-            + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                - 27.0 * C2.powf(2.0) * lux
-                + 72.0 * C3 * C1 * lux
-                + (-4.0 * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux).powf(3.0)
-                    + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                        - 27.0 * C2.powf(2.0) * lux
-                        + 72.0 * C3 * C1 * lux)
-                        .powf(2.0))
-                .sqrt())
-            .powf(1.0 / 3.0)
-                / (3.0 * 2.0_f32.powf(1.0 / 3.0) * C3))
-            .sqrt()
-            / 2.0
-        + (C2.powf(2.0) / (2.0 * C3.powf(2.0))
-            - (4.0 * C1) / (3.0 * C3)
-            - (2.0_f32.powf(1.0 / 3.0) * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux))
-                / (3.0
-                    * C3
-                    * (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                        - 27.0 * C2.powf(2.0) * lux
-                        + 72.0 * C3 * C1 * lux
-                        + (-4.0 * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux).powf(3.0)
-                            + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0
-                                + 27.0 * C3 * C0.powf(2.0)
-                                - 27.0 * C2.powf(2.0) * lux
-                                + 72.0 * C3 * C1 * lux)
-                                .powf(2.0))
-                        .sqrt())
-                    .powf(1.0 / 3.0))
-            - (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                - 27.0 * C2.powf(2.0) * lux
-                + 72.0 * C3 * C1 * lux
-                + (-4.0 * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux).powf(3.0)
-                    + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                        - 27.0 * C2.powf(2.0) * lux
-                        + 72.0 * C3 * C1 * lux)
-                        .powf(2.0))
-                .sqrt())
-            .powf(1.0 / 3.0)
-                / (3.0 * 2.0_f32.powf(1.0 / 3.0) * C3)
-            - (-(C2.powf(3.0) / C3.powf(3.0)) + (4.0 * C2 * C1) / C3.powf(2.0) - (8.0 * C0) / C3)
-                / (4.0
-                    * (C2.powf(2.0) / (4.0 * C3.powf(2.0)) - (2.0 * C1) / (3.0 * C3)
-                        + (2.0_f32.powf(1.0 / 3.0)
-                            * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux))
-                            / (3.0
-                                * C3
-                                * (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0
-                                    + 27.0 * C3 * C0.powf(2.0)
-                                    - 27.0 * C2.powf(2.0) * lux
-                                    + 72.0 * C3 * C1 * lux
-                                    + (-4.0
-                                        * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux)
-                                            .powf(3.0)
-                                        + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0
-                                            + 27.0 * C3 * C0.powf(2.0)
-                                            - 27.0 * C2.powf(2.0) * lux
-                                            + 72.0 * C3 * C1 * lux)
-                                            .powf(2.0))
-                                    .sqrt())
-                                .powf(1.0 / 3.0))
-                        + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0 + 27.0 * C3 * C0.powf(2.0)
-                            - 27.0 * C2.powf(2.0) * lux
-                            + 72.0 * C3 * C1 * lux
-                            + (-4.0
-                                * (C1.powf(2.0) - 3.0 * C2 * C0 - 12.0 * C3 * lux).powf(3.0)
-                                + (2.0 * C1.powf(3.0) - 9.0 * C2 * C1 * C0
-                                    + 27.0 * C3 * C0.powf(2.0)
-                                    - 27.0 * C2.powf(2.0) * lux
-                                    + 72.0 * C3 * C1 * lux)
-                                    .powf(2.0))
-                            .sqrt())
-                        .powf(1.0 / 3.0)
-                            / (3.0 * 2.0_f32.powf(1.0 / 3.0) * C3))
-                        .sqrt()))
-        .sqrt()
-            / 2.0
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polynomial_f64(x: f64) -> f64 {
+        let c = COEFFICIENTS.map(f64::from);
+        x * (c[0] + x * (c[1] + x * (c[2] + x * c[3])))
+    }
+
+    /// Root of the compensation formula found by bisection in `f64`.
+    fn exact_inverse(y: f64) -> f64 {
+        let (mut low, mut high) = (0.0, y);
+        for _ in 0..200 {
+            let mid = (low + high) / 2.0;
+            if polynomial_f64(mid) > y {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        low
+    }
+
+    #[test]
+    fn application_note_formula_is_increasing() {
+        assert!(is_increasing(&COEFFICIENTS));
+        assert!(is_increasing(&[1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn decreasing_polynomials_are_rejected() {
+        // Decreasing above about 6300 lx.
+        assert!(!is_increasing(&[1.0, 0.0, 0.0, -1e-12]));
+        // Decreasing between about 3800 lx and 26000 lx only.
+        assert!(!is_increasing(&[1.0, -1.5e-4, 3.3333e-9, 0.0]));
+        // Decreasing between about 4200 lx and 18800 lx only, with the
+        // vertex of the second derivative at 50000 lx.
+        assert!(!is_increasing(&[1.0, -1.5e-4, 5e-9, -2.5e-14]));
+        assert!(!is_increasing(&[-1.0, 0.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn inverse_polynomial_is_accurate_over_the_output_range() {
+        // Up to the compensated value of 65535 counts at 25 ms and 1/8 gain.
+        let max = polynomial(&COEFFICIENTS, 65535.0 * 1.8432);
+        let mut y = 1.0_f32;
+        while y < max {
+            let exact = exact_inverse(f64::from(y));
+            let error = (f64::from(inverse_polynomial(&COEFFICIENTS, y)) - exact) / exact;
+            assert!(error.abs() < 0.14e-6, "{y} lx: {error}");
+            y *= 1.001;
+        }
+    }
 }
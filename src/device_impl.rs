@@ -1,99 +1,133 @@
 #[cfg(feature = "lux_as_f32")]
-use crate::calculate_raw_threshold_value;
+use crate::correction::{checked_raw_threshold_value, correct_lux, get_lux_raw_conversion_factor};
+#[cfg(feature = "lux_as_u32")]
+use crate::millilux::checked_raw_threshold_value_millilux_with_model;
+#[cfg(feature = "lux_as_u32")]
+use crate::millilux::{convert_raw_to_millilux, correct_millilux};
 #[cfg(feature = "lux_as_f32")]
-use crate::correction::{correct_high_lux, get_lux_raw_conversion_factor};
+use crate::{Calibration, Lux};
 use crate::{
-    Config, Error, FaultCount, Gain, IntegrationTime, InterruptStatus, PowerSavingMode, Veml7700,
-    DEVICE_ADDRESS,
+    ChangeWindow, Config, CorrectionModel, FaultCount, Gain, IntegrationTime, Measurement,
+    PowerSavingMode, RangeAdjustment, RangeStatus, SlaveAddr, Threshold,
 };
-#[cfg(feature = "is_sync")]
-use embedded_hal::i2c::{ErrorType, I2c, SevenBitAddress};
-#[cfg(not(feature = "is_sync"))]
-use embedded_hal_async::i2c::{ErrorType, I2c, SevenBitAddress};
-use maybe_async::maybe_async;
 
-struct Register;
+pub(crate) struct Register;
 impl Register {
-    const ALS_CONF: u8 = 0x00;
-    const ALS_WH: u8 = 0x01;
-    const ALS_WL: u8 = 0x02;
-    const PSM: u8 = 0x03;
-    const ALS: u8 = 0x04;
-    const WHITE: u8 = 0x05;
-    const ALS_INT: u8 = 0x06;
+    pub(crate) const ALS_CONF: u8 = 0x00;
+    pub(crate) const ALS_WH: u8 = 0x01;
+    pub(crate) const ALS_WL: u8 = 0x02;
+    pub(crate) const PSM: u8 = 0x03;
+    pub(crate) const ALS: u8 = 0x04;
+    pub(crate) const WHITE: u8 = 0x05;
+    pub(crate) const ALS_INT: u8 = 0x06;
+    pub(crate) const ID: u8 = 0x07;
 }
 
-struct BitFlags;
+pub(crate) struct BitFlags;
 impl BitFlags {
-    const ALS_SD: u16 = 0x01;
-    const ALS_INT_EN: u16 = 0x02;
-    const PSM_EN: u16 = 0x01;
-    const INT_TH_LOW: u16 = 1 << 15;
-    const INT_TH_HIGH: u16 = 1 << 14;
+    pub(crate) const ALS_SD: u16 = 0x01;
+    pub(crate) const ALS_INT_EN: u16 = 0x02;
+    pub(crate) const PSM_EN: u16 = 0x01;
+    pub(crate) const INT_TH_LOW: u16 = 1 << 15;
+    pub(crate) const INT_TH_HIGH: u16 = 1 << 14;
 }
 
-impl Config {
-    fn with_high(self, mask: u16) -> Self {
-        Config {
-            bits: self.bits | mask,
+/// Device ID code in the low byte of the ID register.
+pub(crate) const DEVICE_ID: u8 = 0x81;
+
+impl SlaveAddr {
+    pub(crate) fn addr(self) -> u8 {
+        match self {
+            SlaveAddr::Default => 0x10,
+            SlaveAddr::Alternative => 0x48,
         }
     }
-    fn with_low(self, mask: u16) -> Self {
-        Config {
-            bits: self.bits & !mask,
+    #[cfg(any(feature = "sim", test))]
+    pub(crate) fn address_option(self) -> u8 {
+        match self {
+            SlaveAddr::Default => 0xC4,
+            SlaveAddr::Alternative => 0xD4,
         }
     }
 }
 
-impl<I2C> Veml7700<I2C>
-where
-    I2C: I2c<SevenBitAddress>,
-    I2C::Error: Into<Error<I2C::Error>>,
-{
-    /// Create new instance of the VEML6040 device.
-    pub fn new(i2c: I2C) -> Self {
-        Veml7700 {
-            i2c,
-            config: Config {
-                bits: BitFlags::ALS_SD,
-            },
-            gain: Gain::One,
-            it: IntegrationTime::_100ms,
+impl CorrectionModel {
+    /// Whether the coefficients of a custom polynomial are finite and it is
+    /// strictly increasing over the range of the sensor.
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            #[cfg(feature = "lux_as_f32")]
+            CorrectionModel::Custom(coefficients) => {
+                coefficients.iter().all(|c| c.is_finite())
+                    && crate::correction::is_increasing(coefficients)
+            }
+            _ => true,
         }
     }
+}
 
-    /// Destroy driver instance, return I²C bus instance.
-    pub fn destroy(self) -> I2C {
-        self.i2c
+#[cfg(feature = "lux_as_f32")]
+impl Calibration {
+    pub(crate) fn is_valid(&self) -> bool {
+        let valid_scale = |scale: f32| scale > 0.0 && scale.is_finite();
+        valid_scale(self.scale)
+            && self.white_scale.map_or(true, valid_scale)
+            && self.offset.is_finite()
+    }
+
+    pub(crate) fn apply(&self, lux: f32) -> f32 {
+        (lux * self.scale + self.offset).max(0.0)
     }
-}
 
-impl<I2C> Veml7700<I2C>
-where
-    I2C: I2c<SevenBitAddress>,
-    I2C::Error: Into<Error<I2C::Error>>,
-{
-    /// Enable the device.
+    /// Like `apply()`, calculated in `f64` to keep the resolution of large
+    /// millilux values.
+    #[cfg(feature = "lux_as_u32")]
+    pub(crate) fn apply_millilux(&self, millilux: u32) -> u32 {
+        // This saturates.
+        (f64::from(millilux) * f64::from(self.scale) + f64::from(self.offset) * 1000.0) as u32
+    }
+
+    pub(crate) fn apply_white(&self, raw: u16) -> f32 {
+        f32::from(raw) * self.white_scale.unwrap_or(self.scale)
+    }
+
+    /// Convert a threshold outside of the window to the one the sensor sees.
     ///
-    /// Note that when activating the sensor a wait time of 4 ms should be
-    /// observed before the first measurement is picked up to allow for a
-    /// correct start of the signal processor and oscillator.
-    #[maybe_async]
-    pub async fn enable(&mut self) -> Result<(), Error<I2C::Error>> {
-        let config = self.config.with_low(BitFlags::ALS_SD);
-        self.set_config(config).await
+    /// Thresholds below the offset become 0. Invalid values stay invalid.
+    pub(crate) fn uncalibrated(&self, threshold: Threshold) -> Threshold {
+        match threshold {
+            Threshold::Raw(_) => threshold,
+            Threshold::Lux(lux) => Threshold::Lux(Lux(self.invert(lux.0))),
+            // Keep the exact integer conversion if there is nothing to invert.
+            #[cfg(feature = "lux_as_u32")]
+            Threshold::Millilux(_) if *self == Calibration::default() => threshold,
+            #[cfg(feature = "lux_as_u32")]
+            Threshold::Millilux(millilux) => {
+                Threshold::Lux(Lux(self.invert(millilux as f32 / 1000.0)))
+            }
+        }
     }
 
-    /// Disable the device (shutdown).
-    #[maybe_async]
-    pub async fn disable(&mut self) -> Result<(), Error<I2C::Error>> {
-        let config = self.config.with_high(BitFlags::ALS_SD);
-        self.set_config(config).await
+    fn invert(&self, lux: f32) -> f32 {
+        if lux >= 0.0 {
+            ((lux - self.offset) / self.scale).max(0.0)
+        } else {
+            lux
+        }
     }
+}
+
+/// Wait time after enabling the device before the first measurement starts.
+pub(crate) const STARTUP_TIME_US: u32 = 4000;
+/// ALS counts at or below which auto ranging increases the sensitivity.
+pub(crate) const AUTO_RANGE_LOW_COUNTS: u16 = 100;
+/// ALS counts above which auto ranging decreases the integration time.
+pub(crate) const AUTO_RANGE_HIGH_COUNTS: u16 = 10000;
+/// ALS counts from which a measurement is considered saturated.
+pub(crate) const SATURATION_COUNTS: u16 = 65000;
 
-    /// Set the integration time.
-    #[maybe_async]
-    pub async fn set_integration_time(&mut self, it: IntegrationTime) -> Result<(), Error<I2C::Error>> {
+impl Config {
+    pub(crate) fn with_integration_time(self, it: IntegrationTime) -> Self {
         let mask = match it {
             IntegrationTime::_25ms => 0b1100,
             IntegrationTime::_50ms => 0b1000,
@@ -102,205 +136,239 @@ where
             IntegrationTime::_400ms => 0b0010,
             IntegrationTime::_800ms => 0b0011,
         };
-        let config = self.config.bits & !(0b1111 << 6) | (mask << 6);
-        self.set_config(Config { bits: config }).await?;
-        self.it = it;
-        Ok(())
+        Config {
+            bits: self.bits & !(0b1111 << 6) | (mask << 6),
+        }
     }
-
-    /// Set the gain.
-    #[maybe_async]
-    pub async fn set_gain(&mut self, gain: Gain) -> Result<(), Error<I2C::Error>> {
+    pub(crate) fn with_gain(self, gain: Gain) -> Self {
         let mask = match gain {
             Gain::One => 0,
             Gain::Two => 1,
             Gain::OneEighth => 2,
             Gain::OneQuarter => 3,
         };
-        let config = self.config.bits & !(0b11 << 11) | mask << 11;
-        self.set_config(Config { bits: config }).await?;
-        self.gain = gain;
-        Ok(())
+        Config {
+            bits: self.bits & !(0b11 << 11) | mask << 11,
+        }
     }
-
-    /// Set the number of times a threshold crossing must happen consecutively
-    /// to trigger an interrupt.
-    #[maybe_async]
-    pub async fn set_fault_count(&mut self, fc: FaultCount) -> Result<(), Error<I2C::Error>> {
+    pub(crate) fn with_fault_count(self, fc: FaultCount) -> Self {
         let mask = match fc {
             FaultCount::One => 0,
             FaultCount::Two => 1,
             FaultCount::Four => 2,
             FaultCount::Eight => 3,
         };
-        let config = self.config.bits & !(0b11 << 4) | mask << 4;
-        self.set_config(Config { bits: config }).await
+        Config {
+            bits: self.bits & !(0b11 << 4) | mask << 4,
+        }
     }
-
-    /// Enable interrupt generation.
-    #[maybe_async]
-    pub async fn enable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
-        let config = self.config.with_high(BitFlags::ALS_INT_EN);
-        self.set_config(config).await
+    pub(crate) fn integration_time(self) -> IntegrationTime {
+        match (self.bits >> 6) & 0b1111 {
+            0b1100 => IntegrationTime::_25ms,
+            0b1000 => IntegrationTime::_50ms,
+            0b0001 => IntegrationTime::_200ms,
+            0b0010 => IntegrationTime::_400ms,
+            0b0011 => IntegrationTime::_800ms,
+            // 0b0000 and the reserved values
+            _ => IntegrationTime::_100ms,
+        }
     }
-
-    /// Disable interrupt generation.
-    #[maybe_async]
-    pub async fn disable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
-        let config = self.config.with_low(BitFlags::ALS_INT_EN);
-        self.set_config(config).await
+    pub(crate) fn gain(self) -> Gain {
+        match (self.bits >> 11) & 0b11 {
+            0 => Gain::One,
+            1 => Gain::Two,
+            2 => Gain::OneEighth,
+            _ => Gain::OneQuarter,
+        }
     }
-
-    /// Set the ALS high threshold in raw format
-    #[maybe_async]
-    pub async fn set_high_threshold_raw(&mut self, threshold: u16) -> Result<(), Error<I2C::Error>> {
-        Ok(self.write_register(Register::ALS_WH, threshold).await?)
+    pub(crate) fn fault_count(self) -> FaultCount {
+        match (self.bits >> 4) & 0b11 {
+            0 => FaultCount::One,
+            1 => FaultCount::Two,
+            2 => FaultCount::Four,
+            _ => FaultCount::Eight,
+        }
     }
-
-    /// Set the ALS low threshold in raw format
-    #[maybe_async]
-    pub async fn set_low_threshold_raw(&mut self, threshold: u16) -> Result<(), Error<I2C::Error>> {
-        Ok(self.write_register(Register::ALS_WL, threshold).await?)
+    pub(crate) fn with_high(self, mask: u16) -> Self {
+        Config {
+            bits: self.bits | mask,
+        }
     }
-
-    /// Set the ALS high threshold in lux.
-    ///
-    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
-    /// the inverse of the compensation formula is applied (this involves
-    /// quite some math).
-    #[cfg(feature = "lux_as_f32")]
-    #[maybe_async]
-    pub async fn set_high_threshold_lux(&mut self, lux: f32) -> Result<(), Error<I2C::Error>> {
-        let raw = self.calculate_raw_threshold_value(lux);
-        self.set_high_threshold_raw(raw).await
+    pub(crate) fn with_low(self, mask: u16) -> Self {
+        Config {
+            bits: self.bits & !mask,
+        }
     }
-    // TODO make a const-able version for pre-calculating the raw-threshold_lux
-
-    /// Set the ALS low threshold in lux.
-    ///
-    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
-    /// the inverse of the compensation formula is applied (this involves
-    /// quite some math).
-    #[cfg(feature = "lux_as_f32")]
-    #[maybe_async]
-    pub async fn set_low_threshold_lux(&mut self, lux: f32) -> Result<(), Error<I2C::Error>> {
-        let raw = self.calculate_raw_threshold_value(lux);
-        self.set_low_threshold_raw(raw).await
+    pub(crate) fn with_flag(self, mask: u16, value: bool) -> Self {
+        if value {
+            self.with_high(mask)
+        } else {
+            self.with_low(mask)
+        }
     }
+}
 
-    /// Calculate raw value for threshold applying compensation if necessary.
-    ///
-    /// This takes into consideration the configured integration time and gain
-    /// and compensates the lux value if necessary.
-    ///
-    /// For values higher than 1000 lx and 1/4 or 1/8 gain, the inverse of the
-    /// compensation formula is applied. This involves quite some math so it
-    /// may be interesting to calculate the threshold values ahead of time.
-    #[cfg(feature = "lux_as_f32")]
-    pub fn calculate_raw_threshold_value(&self, lux: f32) -> u16 {
-        calculate_raw_threshold_value(self.it, self.gain, lux)
-    }
+pub(crate) fn encode_power_saving_mode(psm: Option<PowerSavingMode>) -> u16 {
+    let mask = match psm {
+        None => return 0,
+        Some(PowerSavingMode::One) => 0,
+        Some(PowerSavingMode::Two) => 1,
+        Some(PowerSavingMode::Three) => 2,
+        Some(PowerSavingMode::Four) => 3,
+    };
+    BitFlags::PSM_EN | mask << 1
+}
 
-    /// Enable the power-saving mode
-    #[maybe_async]
-    pub async fn enable_power_saving(&mut self, psm: PowerSavingMode) -> Result<(), Error<I2C::Error>> {
-        let mask = match psm {
-            PowerSavingMode::One => 0,
-            PowerSavingMode::Two => 1,
-            PowerSavingMode::Three => 2,
-            PowerSavingMode::Four => 3,
-        };
-        let value = BitFlags::PSM_EN | mask << 1;
-        Ok(self.write_register(Register::PSM, value).await?)
+pub(crate) fn decode_power_saving_mode(bits: u16) -> Option<PowerSavingMode> {
+    if bits & BitFlags::PSM_EN == 0 {
+        return None;
     }
-
-    /// Disable the power-saving mode
-    #[maybe_async]
-    pub async fn disable_power_saving(&mut self) -> Result<(), Error<I2C::Error>> {
-        Ok(self.write_register(Register::PSM, 0).await?)
+    match (bits >> 1) & 0b11 {
+        0 => Some(PowerSavingMode::One),
+        1 => Some(PowerSavingMode::Two),
+        2 => Some(PowerSavingMode::Three),
+        _ => Some(PowerSavingMode::Four),
     }
+}
 
-    #[maybe_async]
-    async fn set_config(&mut self, config: Config) -> Result<(), Error<I2C::Error>> {
-        self.write_register(Register::ALS_CONF, config.bits).await?;
-        self.config = config;
-        Ok(())
-    }
+/// Time from the start of one measurement to the start of the next one.
+pub(crate) fn refresh_time_us(it: IntegrationTime, psm: Option<PowerSavingMode>) -> u32 {
+    let wait_us = psm.map_or(0, |psm| u32::from(psm.wait_time_ms()) * 1000);
+    it.as_us() + wait_us
+}
 
-    #[maybe_async]
-    async fn write_register(
-        &mut self,
-        register: u8,
-        value: u16,
-    ) -> Result<(), <I2C as ErrorType>::Error> {
-        self.i2c
-            .write(DEVICE_ADDRESS, &[register, value as u8, (value >> 8) as u8]).await
+pub(crate) fn next_higher_gain(gain: Gain) -> Option<Gain> {
+    match gain {
+        Gain::OneEighth => Some(Gain::OneQuarter),
+        Gain::OneQuarter => Some(Gain::One),
+        Gain::One => Some(Gain::Two),
+        Gain::Two => None,
     }
 }
 
-impl<I2C> Veml7700<I2C>
-where
-    I2C: I2c<SevenBitAddress>,
-    I2C::Error: Into<Error<I2C::Error>>,
-{
-    /// Read whether an interrupt has occurred.
-    ///
-    /// Note that the interrupt status is updated at the same rate as the
-    /// measurements. Once triggered, flags will stay true until a measurement
-    /// is taken which does not exceed the threshold.
-    #[maybe_async]
-    pub async fn read_interrupt_status(&mut self) -> Result<InterruptStatus, Error<I2C::Error>> {
-        let data = self.read_register(Register::ALS_INT).await?;
-        Ok(InterruptStatus {
-            was_too_low: (data & BitFlags::INT_TH_LOW) != 0,
-            was_too_high: (data & BitFlags::INT_TH_HIGH) != 0,
-        })
+pub(crate) fn next_longer_integration_time(it: IntegrationTime) -> Option<IntegrationTime> {
+    match it {
+        IntegrationTime::_25ms => Some(IntegrationTime::_50ms),
+        IntegrationTime::_50ms => Some(IntegrationTime::_100ms),
+        IntegrationTime::_100ms => Some(IntegrationTime::_200ms),
+        IntegrationTime::_200ms => Some(IntegrationTime::_400ms),
+        IntegrationTime::_400ms => Some(IntegrationTime::_800ms),
+        IntegrationTime::_800ms => None,
     }
+}
 
-    /// Read ALS high resolution output data in raw format
-    #[maybe_async]
-    pub async fn read_raw(&mut self) -> Result<u16, Error<I2C::Error>> {
-        self.read_register(Register::ALS).await
+pub(crate) fn next_shorter_integration_time(it: IntegrationTime) -> Option<IntegrationTime> {
+    match it {
+        IntegrationTime::_25ms => None,
+        IntegrationTime::_50ms => Some(IntegrationTime::_25ms),
+        IntegrationTime::_100ms => Some(IntegrationTime::_50ms),
+        IntegrationTime::_200ms => Some(IntegrationTime::_100ms),
+        IntegrationTime::_400ms => Some(IntegrationTime::_200ms),
+        IntegrationTime::_800ms => Some(IntegrationTime::_400ms),
     }
+}
 
-    /// Read ALS high resolution output data converted to lux
-    ///
-    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
-    /// the following compensation formula is applied:
-    /// `lux = 6.0135e-13*(lux^4) - 9.3924e-9*(lux^3) + 8.1488e-5*(lux^2) + 1.0023*lux`
-    #[cfg(feature = "lux_as_f32")]
-    #[maybe_async]
-    pub async fn read_lux(&mut self) -> Result<f32, Error<I2C::Error>> {
-        let raw = self.read_register(Register::ALS).await?;
-        Ok(self.convert_raw_als_to_lux(raw))
+/// Raw value of a threshold for the given settings, if it can be represented.
+#[cfg_attr(
+    not(any(feature = "lux_as_f32", feature = "lux_as_u32")),
+    allow(unused_variables)
+)]
+pub(crate) fn raw_threshold_value(
+    threshold: Threshold,
+    model: CorrectionModel,
+    it: IntegrationTime,
+    gain: Gain,
+) -> Option<u16> {
+    match threshold {
+        Threshold::Raw(raw) => Some(raw),
+        #[cfg(feature = "lux_as_f32")]
+        Threshold::Lux(lux) => checked_raw_threshold_value(model, it, gain, lux.0),
+        #[cfg(feature = "lux_as_u32")]
+        Threshold::Millilux(millilux) => {
+            checked_raw_threshold_value_millilux_with_model(model, it, gain, millilux)
+        }
     }
+}
 
-    /// Calculate lux value for a raw ALS measurement.
-    ///
-    /// This takes into consideration the configured integration time and gain
-    /// and compensates the lux value if necessary.
-    ///
-    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
-    /// the following compensation formula is applied:
-    /// `lux = 6.0135e-13*(lux^4) - 9.3924e-9*(lux^3) + 8.1488e-5*(lux^2) + 1.0023*lux`
-    #[cfg(feature = "lux_as_f32")]
-    pub fn convert_raw_als_to_lux(&self, raw_als: u16) -> f32 {
-        convert_raw_als_to_lux(self.it, self.gain, raw_als)
+/// Low and high thresholds of a change window around an ALS measurement.
+pub(crate) fn change_window_thresholds(
+    window: ChangeWindow,
+    measurement: &Measurement,
+) -> (Threshold, Threshold) {
+    match window {
+        ChangeWindow::Percent(percent) => percent_window_thresholds(percent, measurement),
+        ChangeWindow::Raw(delta) => (
+            Threshold::Raw(measurement.raw.saturating_sub(delta)),
+            Threshold::Raw(measurement.raw.saturating_add(delta)),
+        ),
+        #[cfg(feature = "lux_as_f32")]
+        ChangeWindow::Lux(delta) => (
+            Threshold::Lux(Lux((measurement.lux.0 - delta.0).max(0.0))),
+            Threshold::Lux(Lux(measurement.lux.0 + delta.0)),
+        ),
+        #[cfg(feature = "lux_as_u32")]
+        ChangeWindow::Millilux(delta) => (
+            Threshold::Millilux(measurement.millilux.saturating_sub(delta)),
+            Threshold::Millilux(measurement.millilux.saturating_add(delta)),
+        ),
     }
+}
 
-    /// Read white channel measurement
-    #[maybe_async]
-    pub async fn read_white(&mut self) -> Result<u16, Error<I2C::Error>> {
-        self.read_register(Register::WHITE).await
+// The percentage is applied to the light level rather than to the raw counts
+// where possible, as these are not proportional at 1/4 and 1/8 gain.
+#[cfg(feature = "lux_as_f32")]
+fn percent_window_thresholds(percent: u8, measurement: &Measurement) -> (Threshold, Threshold) {
+    let lux = measurement.lux.0;
+    let delta = lux * f32::from(percent) / 100.0;
+    (
+        Threshold::Lux(Lux((lux - delta).max(0.0))),
+        Threshold::Lux(Lux(lux + delta)),
+    )
+}
+
+#[cfg(all(feature = "lux_as_u32", not(feature = "lux_as_f32")))]
+fn percent_window_thresholds(percent: u8, measurement: &Measurement) -> (Threshold, Threshold) {
+    let millilux = measurement.millilux;
+    let delta = (u64::from(millilux) * u64::from(percent) / 100) as u32;
+    (
+        Threshold::Millilux(millilux.saturating_sub(delta)),
+        Threshold::Millilux(millilux.saturating_add(delta)),
+    )
+}
+
+#[cfg(not(any(feature = "lux_as_f32", feature = "lux_as_u32")))]
+fn percent_window_thresholds(percent: u8, measurement: &Measurement) -> (Threshold, Threshold) {
+    let raw = measurement.raw;
+    let delta = (u32::from(raw) * u32::from(percent) / 100).min(u32::from(u16::MAX)) as u16;
+    (
+        Threshold::Raw(raw.saturating_sub(delta)),
+        Threshold::Raw(raw.saturating_add(delta)),
+    )
+}
+
+pub(crate) fn range_status(raw: u16) -> RangeStatus {
+    if raw >= SATURATION_COUNTS {
+        RangeStatus::Saturated
+    } else if raw <= AUTO_RANGE_LOW_COUNTS {
+        RangeStatus::UnderRange
+    } else {
+        RangeStatus::InRange
     }
+}
 
-    #[maybe_async]
-    async fn read_register(&mut self, register: u8) -> Result<u16, Error<I2C::Error>> {
-        let mut data = [0; 2];
-        self.i2c
-            .write_read(DEVICE_ADDRESS, &[register], &mut data).await
-            .map_err(Error::I2C)
-            .and(Ok(u16::from(data[0]) | u16::from(data[1]) << 8))
+pub(crate) fn range_adjustment(
+    status: RangeStatus,
+    gain: Gain,
+    it: IntegrationTime,
+) -> Option<RangeAdjustment> {
+    match status {
+        RangeStatus::InRange => None,
+        RangeStatus::UnderRange => (next_higher_gain(gain).is_some()
+            || next_longer_integration_time(it).is_some())
+        .then_some(RangeAdjustment::IncreaseSensitivity),
+        RangeStatus::Saturated => (gain != Gain::OneEighth
+            || next_shorter_integration_time(it).is_some())
+        .then_some(RangeAdjustment::DecreaseSensitivity),
     }
 }
 
@@ -309,13 +377,53 @@ where
 /// For values higher than 1000 lx and 1/4 or 1/8 gain,
 /// the following compensation formula is applied:
 /// `lux = 6.0135e-13*(lux^4) - 9.3924e-9*(lux^3) + 8.1488e-5*(lux^2) + 1.0023*lux`
+///
+/// This applies the default `CorrectionModel::LowGain`. See the driver method
+/// of the same name for the correction model selected on the driver.
 #[cfg(feature = "lux_as_f32")]
-pub fn convert_raw_als_to_lux(it: IntegrationTime, gain: Gain, raw_als: u16) -> f32 {
-    let factor = get_lux_raw_conversion_factor(it, gain);
-    let lux = f32::from(raw_als) * factor;
-    if (gain == Gain::OneQuarter || gain == Gain::OneEighth) && lux > 1000.0 {
-        correct_high_lux(lux)
-    } else {
-        lux
-    }
+pub fn convert_raw_als_to_lux(it: IntegrationTime, gain: Gain, raw_als: u16) -> Lux {
+    Lux(raw_to_lux(CorrectionModel::LowGain, it, gain, raw_als))
+}
+
+/// Calculate lux value for a raw ALS measurement with the given correction
+/// model.
+#[cfg(feature = "lux_as_f32")]
+pub(crate) fn raw_to_lux(
+    model: CorrectionModel,
+    it: IntegrationTime,
+    gain: Gain,
+    raw_als: u16,
+) -> f32 {
+    let lux = f32::from(raw_als) * get_lux_raw_conversion_factor(it, gain);
+    correct_lux(model, gain, lux)
+}
+
+/// Calculate millilux value for a raw ALS measurement.
+///
+/// This is the integer counterpart of `convert_raw_als_to_lux()` and uses the
+/// same compensation formula for values higher than 1000 lx and 1/4 or 1/8
+/// gain, evaluated with exact integer arithmetic. The results match the `f32`
+/// version within 2 ppm or 1 millilux, whichever is larger.
+///
+/// The compensated value saturates at `u32::MAX` (about 4295 klx), which the
+/// compensation formula reaches for uncompensated values of roughly 52000 lx.
+///
+/// This applies the default `CorrectionModel::LowGain`. See the driver method
+/// of the same name for the correction model selected on the driver.
+#[cfg(feature = "lux_as_u32")]
+pub fn convert_raw_als_to_millilux(it: IntegrationTime, gain: Gain, raw_als: u16) -> u32 {
+    raw_to_millilux(CorrectionModel::LowGain, it, gain, raw_als)
+}
+
+/// Calculate millilux value for a raw ALS measurement with the given
+/// correction model.
+#[cfg(feature = "lux_as_u32")]
+pub(crate) fn raw_to_millilux(
+    model: CorrectionModel,
+    it: IntegrationTime,
+    gain: Gain,
+    raw_als: u16,
+) -> u32 {
+    let millilux = convert_raw_to_millilux(it, gain, raw_als);
+    correct_millilux(model, gain, millilux)
 }
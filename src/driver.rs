@@ -0,0 +1,1078 @@
+use super::interface::{read_register, write_register};
+use super::{maybe_async, DelayNs, I2c, SevenBitAddress};
+#[cfg(feature = "lux_as_f32")]
+use crate::device_impl::raw_to_lux;
+#[cfg(feature = "lux_as_u32")]
+use crate::device_impl::raw_to_millilux;
+use crate::device_impl::{
+    change_window_thresholds, decode_power_saving_mode, encode_power_saving_mode, next_higher_gain,
+    next_longer_integration_time, next_shorter_integration_time, range_adjustment, range_status,
+    raw_threshold_value, refresh_time_us, BitFlags, Register, AUTO_RANGE_HIGH_COUNTS,
+    AUTO_RANGE_LOW_COUNTS, DEVICE_ID, STARTUP_TIME_US,
+};
+#[cfg(feature = "lux_as_f32")]
+use crate::{Calibration, Lux};
+use crate::{
+    ChangeWindow, Config, CorrectionModel, DeviceId, Error, FaultCount, Gain, IntegrationTime,
+    InterruptStatus, Measurement, OneShotMeasurement, PowerSavingMode, RangeStatus, Settings,
+    SlaveAddr, Threshold, Veml7700Config,
+};
+use embedded_hal::i2c::{Error as _, ErrorKind};
+
+/// VEML6030 device driver.
+///
+/// The VEML6030 has the same register map as the VEML7700 and is driven by the
+/// same driver. See `Veml7700::new_with_address()` for selecting the slave
+/// address set with its ADDR pin.
+pub type Veml6030<I2C> = Veml7700<I2C>;
+
+/// VEML7700 device driver.
+#[derive(Debug)]
+pub struct Veml7700<I2C> {
+    /// The concrete I²C device implementation.
+    i2c: I2C,
+    /// The I²C device address.
+    address: u8,
+    config: Config,
+    gain: Gain,
+    it: IntegrationTime,
+    psm: Option<PowerSavingMode>,
+    high_threshold: u16,
+    low_threshold: u16,
+    /// Thresholds as set, rewritten in raw format when the gain or the
+    /// integration time change.
+    high_threshold_setting: Threshold,
+    low_threshold_setting: Threshold,
+    /// Window the thresholds are re-programmed to after an interrupt.
+    change_window: Option<ChangeWindow>,
+    /// Whether re-programming the change window failed after the interrupt
+    /// flags were cleared.
+    rearm_pending: bool,
+    correction: CorrectionModel,
+    #[cfg(feature = "lux_as_f32")]
+    calibration: Calibration,
+    /// Time to wait for a measurement taken with the current settings, or
+    /// zero if the last sample is known to be fresh.
+    stale_wait_us: u32,
+    /// While the last sample is stale, time until the measurement in progress
+    /// completes, which may use any of the settings written since.
+    in_progress_us: u32,
+}
+
+impl<I2C> Veml7700<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Create new instance of the VEML7700 device.
+    ///
+    /// This assumes the device is in its power-on state. See
+    /// `new_from_device()` otherwise.
+    pub fn new(i2c: I2C) -> Self {
+        Self::new_with_address(i2c, SlaveAddr::default())
+    }
+
+    /// Create new instance of the device with the given slave address.
+    ///
+    /// Only the VEML6030 supports the alternative address. Like `new()`, this
+    /// assumes the device is in its power-on state. For taking over the
+    /// configuration of a device at the alternative address, call
+    /// `sync_from_device()` afterwards.
+    pub fn new_with_address(i2c: I2C, address: SlaveAddr) -> Self {
+        Veml7700 {
+            i2c,
+            address: address.addr(),
+            config: Config {
+                bits: BitFlags::ALS_SD,
+            },
+            gain: Gain::One,
+            it: IntegrationTime::_100ms,
+            psm: None,
+            high_threshold: 0,
+            low_threshold: 0,
+            high_threshold_setting: Threshold::Raw(0),
+            low_threshold_setting: Threshold::Raw(0),
+            change_window: None,
+            rearm_pending: false,
+            correction: CorrectionModel::LowGain,
+            #[cfg(feature = "lux_as_f32")]
+            calibration: Calibration::new(1.0),
+            stale_wait_us: 0,
+            in_progress_us: 0,
+        }
+    }
+
+    /// Create new instance of the VEML7700 device taking over the
+    /// configuration the device currently has.
+    ///
+    /// Use this instead of `new()` when the device may have been configured
+    /// before, for example after a warm reset of the MCU.
+    /// See `sync_from_device()`.
+    #[maybe_async]
+    pub async fn new_from_device(i2c: I2C) -> Result<Self, Error<I2C::Error>> {
+        let mut device = Self::new(i2c);
+        device.sync_from_device().await?;
+        Ok(device)
+    }
+
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Get the current configuration as known to the driver.
+    ///
+    /// This is decoded from the cached state and does not access the device.
+    /// See `read_settings()` for reading it from the device.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            gain: self.gain,
+            integration_time: self.it,
+            fault_count: self.config.fault_count(),
+            interrupts_enabled: self.config.bits & BitFlags::ALS_INT_EN != 0,
+            enabled: self.config.bits & BitFlags::ALS_SD == 0,
+            power_saving: self.psm,
+            high_threshold: self.high_threshold,
+            low_threshold: self.low_threshold,
+            high_threshold_setting: self.high_threshold_setting,
+            low_threshold_setting: self.low_threshold_setting,
+        }
+    }
+
+    /// Read the current configuration from the device.
+    ///
+    /// This also updates the cached state. See `sync_from_device()`.
+    #[maybe_async]
+    pub async fn read_settings(&mut self) -> Result<Settings, Error<I2C::Error>> {
+        self.sync_from_device().await?;
+        Ok(self.settings())
+    }
+
+    /// Read the ID register.
+    ///
+    /// This register is only documented in newer revisions of the datasheet.
+    /// See `probe()` for checking that the device is a VEML7700.
+    #[maybe_async]
+    pub async fn read_device_id(&mut self) -> Result<DeviceId, Error<I2C::Error>> {
+        let id = self.read_register(Register::ID).await?;
+        Ok(DeviceId {
+            device_id: id as u8,
+            address_option: (id >> 8) as u8,
+        })
+    }
+
+    /// Check that the device is present and is a VEML7700 or VEML6030.
+    ///
+    /// Returns `Error::DeviceNotFound` if the device does not acknowledge
+    /// its address and `Error::WrongId` with the contents of the ID register
+    /// if the device ID code is not the one of these devices. Other bus errors
+    /// are returned as `Error::I2C`.
+    #[maybe_async]
+    pub async fn probe(&mut self) -> Result<DeviceId, Error<I2C::Error>> {
+        let id = self.read_device_id().await.map_err(|e| match e {
+            Error::I2C(e) if matches!(e.kind(), ErrorKind::NoAcknowledge(_)) => {
+                Error::DeviceNotFound
+            }
+            e => e,
+        })?;
+        if id.device_id != DEVICE_ID {
+            let raw = u16::from(id.address_option) << 8 | u16::from(id.device_id);
+            return Err(Error::WrongId(raw));
+        }
+        Ok(id)
+    }
+}
+
+impl<I2C> Veml7700<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Enable the device.
+    ///
+    /// Note that when activating the sensor a wait time of 4 ms should be
+    /// observed before the first measurement is picked up to allow for a
+    /// correct start of the signal processor and oscillator.
+    #[maybe_async]
+    pub async fn enable(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_low(BitFlags::ALS_SD);
+        self.set_config(config).await
+    }
+
+    /// Disable the device (shutdown).
+    #[maybe_async]
+    pub async fn disable(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_high(BitFlags::ALS_SD);
+        self.set_config(config).await
+    }
+
+    /// Set the integration time.
+    ///
+    /// Thresholds set in lux or millilux are rewritten for the new integration
+    /// time. Thresholds that cannot be represented anymore saturate to the
+    /// maximum raw value.
+    #[maybe_async]
+    pub async fn set_integration_time(
+        &mut self,
+        it: IntegrationTime,
+    ) -> Result<(), Error<I2C::Error>> {
+        // Thresholds first, so that samples taken with the new integration
+        // time are never compared against the old thresholds. Samples still
+        // taken with the old one may be compared against the new thresholds.
+        self.rewrite_thresholds(it, self.gain).await?;
+        let config = self.config.with_integration_time(it);
+        self.set_config(config).await?;
+        self.it = it;
+        Ok(())
+    }
+
+    /// Set the gain.
+    ///
+    /// Thresholds set in lux or millilux are rewritten for the new gain.
+    /// Thresholds that cannot be represented anymore saturate to the maximum
+    /// raw value.
+    #[maybe_async]
+    pub async fn set_gain(&mut self, gain: Gain) -> Result<(), Error<I2C::Error>> {
+        self.rewrite_thresholds(self.it, gain).await?;
+        let config = self.config.with_gain(gain);
+        self.set_config(config).await?;
+        self.gain = gain;
+        Ok(())
+    }
+
+    /// Set the non-linearity correction applied to lux values.
+    ///
+    /// The model is used for all conversions to lux and millilux and for all
+    /// thresholds set in lux or millilux. Those are rewritten for the new
+    /// model. Thresholds that cannot be represented anymore saturate to the
+    /// maximum raw value.
+    ///
+    /// Returns `Error::InvalidCorrectionModel` if the coefficients of a custom
+    /// polynomial are not finite or it is not strictly increasing over the
+    /// range of the sensor, up to about 120.8 klx before correction.
+    #[maybe_async]
+    pub async fn set_correction_model(
+        &mut self,
+        model: CorrectionModel,
+    ) -> Result<(), Error<I2C::Error>> {
+        if !model.is_valid() {
+            return Err(Error::InvalidCorrectionModel);
+        }
+        self.correction = model;
+        self.rewrite_thresholds(self.it, self.gain).await
+    }
+
+    /// Set the calibration for a window or cover glass in front of the
+    /// sensor.
+    ///
+    /// The calibration is applied to all conversions to lux and millilux and
+    /// inverted for all thresholds set in lux or millilux, which are rewritten
+    /// for the new calibration. Thresholds that cannot be represented anymore
+    /// saturate to the maximum raw value.
+    ///
+    /// Returns `Error::InvalidCalibration` if a scale factor is not positive
+    /// and finite or the offset is not finite.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_calibration(
+        &mut self,
+        calibration: Calibration,
+    ) -> Result<(), Error<I2C::Error>> {
+        if !calibration.is_valid() {
+            return Err(Error::InvalidCalibration);
+        }
+        self.calibration = calibration;
+        self.rewrite_thresholds(self.it, self.gain).await
+    }
+
+    /// Set the number of times a threshold crossing must happen consecutively
+    /// to trigger an interrupt.
+    #[maybe_async]
+    pub async fn set_fault_count(&mut self, fc: FaultCount) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_fault_count(fc);
+        self.set_config(config).await
+    }
+
+    /// Enable interrupt generation.
+    #[maybe_async]
+    pub async fn enable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_high(BitFlags::ALS_INT_EN);
+        self.set_config(config).await
+    }
+
+    /// Disable interrupt generation.
+    #[maybe_async]
+    pub async fn disable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_low(BitFlags::ALS_INT_EN);
+        self.set_config(config).await
+    }
+
+    /// Generate an interrupt whenever the light level leaves a window around
+    /// the current measurement.
+    ///
+    /// This reads the current measurement, sets the thresholds to `window`
+    /// around it and enables interrupts. Every time `read_interrupt_status()`
+    /// then reports a threshold crossing, the thresholds are set to the same
+    /// window around the new measurement. Thresholds that cannot be
+    /// represented with the current gain and integration time saturate.
+    ///
+    /// Setting thresholds explicitly or applying a configuration ends this
+    /// mode, as does `disable_interrupt_on_change()`.
+    ///
+    /// Returns `Error::NotReady` if the device is shut down and
+    /// `Error::InvalidThreshold` if a window in lux is negative.
+    #[maybe_async]
+    pub async fn enable_interrupt_on_change(
+        &mut self,
+        window: ChangeWindow,
+    ) -> Result<(), Error<I2C::Error>> {
+        #[cfg(feature = "lux_as_f32")]
+        if let ChangeWindow::Lux(delta) = window {
+            if delta.0.is_nan() || delta.0 < 0.0 {
+                return Err(Error::InvalidThreshold);
+            }
+        }
+        if self.config.bits & BitFlags::ALS_SD != 0 {
+            return Err(Error::NotReady);
+        }
+        self.rearm_change_window(window).await?;
+        self.change_window = Some(window);
+        self.enable_interrupts().await
+    }
+
+    /// Stop re-programming the thresholds after interrupts and disable
+    /// interrupt generation.
+    #[maybe_async]
+    pub async fn disable_interrupt_on_change(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.change_window = None;
+        self.rearm_pending = false;
+        self.disable_interrupts().await
+    }
+
+    /// Set the ALS high threshold in raw format
+    #[maybe_async]
+    pub async fn set_high_threshold_raw(
+        &mut self,
+        threshold: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_high_threshold(Threshold::Raw(threshold)).await
+    }
+
+    /// Set the ALS low threshold in raw format
+    #[maybe_async]
+    pub async fn set_low_threshold_raw(&mut self, threshold: u16) -> Result<(), Error<I2C::Error>> {
+        self.set_low_threshold(Threshold::Raw(threshold)).await
+    }
+
+    /// Set the ALS low and high thresholds in raw format.
+    ///
+    /// Returns `Error::InvalidThreshold` if `low` is above `high`.
+    #[maybe_async]
+    pub async fn set_thresholds_raw(
+        &mut self,
+        low: u16,
+        high: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_thresholds(Threshold::Raw(low), Threshold::Raw(high))
+            .await
+    }
+
+    /// Set the ALS high threshold in lux.
+    ///
+    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
+    /// the inverse of the compensation formula is applied.
+    ///
+    /// The threshold is remembered in lux and rewritten whenever the gain or
+    /// the integration time change.
+    ///
+    /// Returns `Error::InvalidThreshold` if the value cannot be represented
+    /// with the current gain and integration time.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_high_threshold_lux(&mut self, lux: Lux) -> Result<(), Error<I2C::Error>> {
+        self.set_high_threshold(Threshold::Lux(lux)).await
+    }
+
+    /// Set the ALS low threshold in lux.
+    ///
+    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
+    /// the inverse of the compensation formula is applied.
+    ///
+    /// The threshold is remembered in lux and rewritten whenever the gain or
+    /// the integration time change.
+    ///
+    /// Returns `Error::InvalidThreshold` if the value cannot be represented
+    /// with the current gain and integration time.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_low_threshold_lux(&mut self, lux: Lux) -> Result<(), Error<I2C::Error>> {
+        self.set_low_threshold(Threshold::Lux(lux)).await
+    }
+
+    /// Set the ALS low and high thresholds in lux.
+    ///
+    /// Returns `Error::InvalidThreshold` if `low` is above `high` or either
+    /// value cannot be represented with the current gain and integration time.
+    /// Nothing is written in that case.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_thresholds_lux(
+        &mut self,
+        low: Lux,
+        high: Lux,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_thresholds(Threshold::Lux(low), Threshold::Lux(high))
+            .await
+    }
+
+    /// Calculate raw value for threshold applying compensation if necessary.
+    ///
+    /// This takes into consideration the configured integration time and gain
+    /// and compensates the lux value if necessary.
+    ///
+    /// The inverse of the selected correction model is applied, which takes a
+    /// few iterations. It may be interesting to calculate the threshold values
+    /// ahead of time, for example with the `const fn`
+    /// `calculate_raw_threshold_value_millilux()`.
+    ///
+    /// Values outside of the range of the ALS register saturate to 0 or
+    /// `u16::MAX`.
+    #[cfg(feature = "lux_as_f32")]
+    pub fn calculate_raw_threshold_value(&self, lux: Lux) -> u16 {
+        self.raw_value(Threshold::Lux(lux), self.it, self.gain)
+            .unwrap_or(if lux.0 > 0.0 { u16::MAX } else { 0 })
+    }
+
+    /// Set the ALS high threshold in millilux.
+    ///
+    /// This is the integer counterpart of `set_high_threshold_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_high_threshold_millilux(
+        &mut self,
+        millilux: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_high_threshold(Threshold::Millilux(millilux)).await
+    }
+
+    /// Set the ALS low threshold in millilux.
+    ///
+    /// This is the integer counterpart of `set_low_threshold_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_low_threshold_millilux(
+        &mut self,
+        millilux: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_low_threshold(Threshold::Millilux(millilux)).await
+    }
+
+    /// Set the ALS low and high thresholds in millilux.
+    ///
+    /// This is the integer counterpart of `set_thresholds_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_thresholds_millilux(
+        &mut self,
+        low: u32,
+        high: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_thresholds(Threshold::Millilux(low), Threshold::Millilux(high))
+            .await
+    }
+
+    /// Calculate raw value for a threshold in millilux applying compensation
+    /// if necessary.
+    ///
+    /// This is the integer counterpart of `calculate_raw_threshold_value()`.
+    #[cfg(feature = "lux_as_u32")]
+    pub fn calculate_raw_threshold_value_millilux(&self, millilux: u32) -> u16 {
+        self.raw_value(Threshold::Millilux(millilux), self.it, self.gain)
+            .unwrap_or(u16::MAX)
+    }
+
+    /// Enable the power-saving mode
+    #[maybe_async]
+    pub async fn enable_power_saving(
+        &mut self,
+        psm: PowerSavingMode,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_power_saving(Some(psm)).await
+    }
+
+    /// Disable the power-saving mode
+    #[maybe_async]
+    pub async fn disable_power_saving(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.set_power_saving(None).await
+    }
+
+    /// Apply a complete configuration.
+    ///
+    /// Thresholds in lux are converted with the gain and integration time of
+    /// `config`. The PSM and threshold registers are only written if they
+    /// change, then ALS_CONF is written once, so that interrupts are only
+    /// enabled once the new thresholds are in place.
+    ///
+    /// Returns `Error::InvalidThreshold` if a threshold cannot be represented
+    /// with the new settings or the low threshold is above the high one.
+    /// Nothing is written in that case.
+    #[maybe_async]
+    pub async fn apply(&mut self, config: Veml7700Config) -> Result<(), Error<I2C::Error>> {
+        let gain = config.gain;
+        let it = config.integration_time;
+        let high_threshold = self
+            .raw_value(config.high_threshold, it, gain)
+            .ok_or(Error::InvalidThreshold)?;
+        let low_threshold = self
+            .raw_value(config.low_threshold, it, gain)
+            .ok_or(Error::InvalidThreshold)?;
+        if low_threshold > high_threshold {
+            return Err(Error::InvalidThreshold);
+        }
+        if config.power_saving != self.psm {
+            self.set_power_saving(config.power_saving).await?;
+        }
+        if high_threshold != self.high_threshold {
+            self.write_high_threshold(high_threshold).await?;
+        }
+        if low_threshold != self.low_threshold {
+            self.write_low_threshold(low_threshold).await?;
+        }
+        self.high_threshold_setting = config.high_threshold;
+        self.low_threshold_setting = config.low_threshold;
+        self.change_window = None;
+        self.rearm_pending = false;
+        let bits = self
+            .config
+            .with_gain(gain)
+            .with_integration_time(it)
+            .with_fault_count(config.fault_count)
+            .with_flag(BitFlags::ALS_INT_EN, config.interrupts_enabled)
+            .with_flag(BitFlags::ALS_SD, !config.enabled);
+        self.set_config(bits).await?;
+        self.gain = gain;
+        self.it = it;
+        Ok(())
+    }
+
+    /// Read the configuration from the device and update the cached state.
+    ///
+    /// This reads the ALS_CONF, PSM, ALS_WH and ALS_WL registers so that
+    /// lux conversions and threshold calculations use the gain and integration
+    /// time the device really has, for example after a warm reset of the MCU
+    /// left the device configured.
+    #[maybe_async]
+    pub async fn sync_from_device(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = Config {
+            bits: self.read_register(Register::ALS_CONF).await?,
+        };
+        let psm = self.read_register(Register::PSM).await?;
+        let high_threshold = self.read_register(Register::ALS_WH).await?;
+        let low_threshold = self.read_register(Register::ALS_WL).await?;
+        self.config = config;
+        self.gain = config.gain();
+        self.it = config.integration_time();
+        self.psm = decode_power_saving_mode(psm);
+        self.high_threshold = high_threshold;
+        self.low_threshold = low_threshold;
+        self.high_threshold_setting = Threshold::Raw(high_threshold);
+        self.low_threshold_setting = Threshold::Raw(low_threshold);
+        self.change_window = None;
+        self.rearm_pending = false;
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn set_high_threshold(&mut self, threshold: Threshold) -> Result<(), Error<I2C::Error>> {
+        let raw = self.checked_raw_threshold_value(threshold)?;
+        self.write_high_threshold(raw).await?;
+        self.high_threshold_setting = threshold;
+        self.change_window = None;
+        self.rearm_pending = false;
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn set_low_threshold(&mut self, threshold: Threshold) -> Result<(), Error<I2C::Error>> {
+        let raw = self.checked_raw_threshold_value(threshold)?;
+        self.write_low_threshold(raw).await?;
+        self.low_threshold_setting = threshold;
+        self.change_window = None;
+        self.rearm_pending = false;
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn set_thresholds(
+        &mut self,
+        low: Threshold,
+        high: Threshold,
+    ) -> Result<(), Error<I2C::Error>> {
+        let low_raw = self.checked_raw_threshold_value(low)?;
+        let high_raw = self.checked_raw_threshold_value(high)?;
+        if low_raw > high_raw {
+            return Err(Error::InvalidThreshold);
+        }
+        self.write_low_threshold(low_raw).await?;
+        self.write_high_threshold(high_raw).await?;
+        self.low_threshold_setting = low;
+        self.high_threshold_setting = high;
+        self.change_window = None;
+        self.rearm_pending = false;
+        Ok(())
+    }
+
+    /// Set the thresholds to `window` around the current measurement.
+    #[maybe_async]
+    async fn rearm_change_window(&mut self, window: ChangeWindow) -> Result<(), Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        let (low, high) = change_window_thresholds(window, &self.measurement(raw));
+        self.low_threshold_setting = low;
+        self.high_threshold_setting = high;
+        self.rewrite_thresholds(self.it, self.gain).await?;
+        self.rearm_pending = false;
+        Ok(())
+    }
+
+    fn checked_raw_threshold_value(&self, threshold: Threshold) -> Result<u16, Error<I2C::Error>> {
+        self.raw_value(threshold, self.it, self.gain)
+            .ok_or(Error::InvalidThreshold)
+    }
+
+    /// Rewrite the thresholds set in lux or millilux for the given gain
+    /// and integration time.
+    #[maybe_async]
+    async fn rewrite_thresholds(
+        &mut self,
+        it: IntegrationTime,
+        gain: Gain,
+    ) -> Result<(), Error<I2C::Error>> {
+        // Values that were valid when set can only be too high now.
+        let high = self
+            .raw_value(self.high_threshold_setting, it, gain)
+            .unwrap_or(u16::MAX);
+        if high != self.high_threshold {
+            self.write_high_threshold(high).await?;
+        }
+        let low = self
+            .raw_value(self.low_threshold_setting, it, gain)
+            .unwrap_or(u16::MAX);
+        if low != self.low_threshold {
+            self.write_low_threshold(low).await?;
+        }
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn write_high_threshold(&mut self, raw: u16) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::ALS_WH, raw).await?;
+        self.high_threshold = raw;
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn write_low_threshold(&mut self, raw: u16) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::ALS_WL, raw).await?;
+        self.low_threshold = raw;
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn set_power_saving(
+        &mut self,
+        psm: Option<PowerSavingMode>,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::PSM, encode_power_saving_mode(psm))
+            .await?;
+        self.psm = psm;
+        Ok(())
+    }
+
+    #[maybe_async]
+    async fn set_config(&mut self, config: Config) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::ALS_CONF, config.bits).await?;
+        self.update_stale_wait(config);
+        self.config = config;
+        Ok(())
+    }
+
+    /// Track how long the ALS register may still hold a sample measured with
+    /// previous settings after writing `config`.
+    fn update_stale_wait(&mut self, config: Config) {
+        if config.bits & BitFlags::ALS_SD != 0 {
+            return;
+        }
+        let refresh_us = refresh_time_us(config.integration_time(), self.psm);
+        if self.config.bits & BitFlags::ALS_SD != 0 {
+            // The first measurement already uses the new settings.
+            self.in_progress_us = STARTUP_TIME_US + refresh_us;
+            self.stale_wait_us = self.in_progress_us;
+        } else if config.gain() != self.config.gain()
+            || config.integration_time() != self.config.integration_time()
+        {
+            // The measurement in progress may still use the previous settings,
+            // or any earlier ones if the sample has been stale since. This
+            // does not add up over repeated changes.
+            let previous_us = refresh_time_us(self.config.integration_time(), self.psm);
+            if self.stale_wait_us == 0 {
+                self.in_progress_us = previous_us;
+            } else {
+                self.in_progress_us = self.in_progress_us.max(previous_us);
+            }
+            self.stale_wait_us = self.in_progress_us + refresh_us;
+        }
+    }
+
+    #[maybe_async]
+    async fn write_register(&mut self, register: u8, value: u16) -> Result<(), Error<I2C::Error>> {
+        write_register(&mut self.i2c, self.address, register, value).await
+    }
+}
+
+impl<I2C> Veml7700<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Read whether an interrupt has occurred.
+    ///
+    /// Note that the interrupt status is updated at the same rate as the
+    /// measurements. Reading it clears the flags and releases the INT pin.
+    /// They are set again once the fault count of consecutive measurements
+    /// exceeds a threshold again.
+    ///
+    /// With `enable_interrupt_on_change()`, the thresholds are re-programmed
+    /// around the current measurement whenever a flag is set. As the flags
+    /// are already cleared by then, the status is returned even if this
+    /// fails. It is retried on the next call, which returns the error
+    /// instead if it fails again, before reading the flags.
+    #[maybe_async]
+    pub async fn read_interrupt_status(&mut self) -> Result<InterruptStatus, Error<I2C::Error>> {
+        if let Some(window) = self.change_window.filter(|_| self.rearm_pending) {
+            self.rearm_change_window(window).await?;
+        }
+        let data = self.read_register(Register::ALS_INT).await?;
+        let status = InterruptStatus {
+            was_too_low: (data & BitFlags::INT_TH_LOW) != 0,
+            was_too_high: (data & BitFlags::INT_TH_HIGH) != 0,
+        };
+        if let Some(window) = self.change_window {
+            if status.was_too_low || status.was_too_high {
+                self.rearm_pending = self.rearm_change_window(window).await.is_err();
+            }
+        }
+        Ok(status)
+    }
+
+    /// Read ALS high resolution output data in raw format
+    ///
+    /// Right after changing the settings, see `read_raw_fresh()`.
+    #[maybe_async]
+    pub async fn read_raw(&mut self) -> Result<u16, Error<I2C::Error>> {
+        self.read_register(Register::ALS).await
+    }
+
+    /// Read ALS high resolution output data in raw format, waiting for
+    /// a measurement taken with the current settings if necessary.
+    ///
+    /// After enabling the device or changing the gain or the integration time,
+    /// the ALS register holds a value measured with the previous settings
+    /// until a full measurement with the new settings has completed. Until
+    /// this has been waited for, `is_sample_stale()` returns `true` and this
+    /// method waits for the measurement in progress and a new full one
+    /// (including the power-saving mode wait time, if enabled) before reading.
+    #[maybe_async]
+    pub async fn read_raw_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<u16, Error<I2C::Error>> {
+        if self.stale_wait_us != 0 {
+            // Allow some margin for the tolerance of the internal oscillator.
+            delay
+                .delay_us(self.stale_wait_us + self.stale_wait_us / 10)
+                .await;
+            self.stale_wait_us = 0;
+        }
+        self.read_raw().await
+    }
+
+    /// Read ALS high resolution output data converted to lux, waiting for
+    /// a measurement taken with the current settings if necessary.
+    ///
+    /// See `read_raw_fresh()` and `read_lux()`.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_lux_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Lux, Error<I2C::Error>> {
+        let raw = self.read_raw_fresh(delay).await?;
+        Ok(self.convert_raw_als_to_lux(raw))
+    }
+
+    /// Read ALS high resolution output data converted to millilux, waiting
+    /// for a measurement taken with the current settings if necessary.
+    ///
+    /// See `read_raw_fresh()` and `read_millilux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn read_millilux_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<u32, Error<I2C::Error>> {
+        let raw = self.read_raw_fresh(delay).await?;
+        Ok(self.convert_raw_als_to_millilux(raw))
+    }
+
+    /// Read an ALS measurement together with the settings it was taken with.
+    ///
+    /// Besides the lux value, the result tells whether the counts are
+    /// saturated or too low to be useful with the current gain and integration
+    /// time and in which direction to adjust them. See `measure_auto_range()`
+    /// for doing this automatically.
+    #[maybe_async]
+    pub async fn read_measurement(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.measurement(raw))
+    }
+
+    /// Read an ALS measurement together with the settings it was taken with,
+    /// waiting for a measurement taken with the current settings if necessary.
+    ///
+    /// See `read_raw_fresh()` and `read_measurement()`.
+    #[maybe_async]
+    pub async fn read_measurement_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Measurement, Error<I2C::Error>> {
+        let raw = self.read_raw_fresh(delay).await?;
+        Ok(self.measurement(raw))
+    }
+
+    /// Read an ALS measurement like `read_measurement()`, but fail for
+    /// saturated counts.
+    ///
+    /// Returns `Error::Saturated` if the status is `RangeStatus::Saturated`.
+    #[maybe_async]
+    pub async fn read_measurement_strict(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        let measurement = self.read_measurement().await?;
+        if measurement.status == RangeStatus::Saturated {
+            return Err(Error::Saturated);
+        }
+        Ok(measurement)
+    }
+
+    /// Return whether the ALS register may still hold a value measured with
+    /// settings older than the current ones.
+    ///
+    /// This is set when enabling the device or changing the gain or the
+    /// integration time and cleared by `read_raw_fresh()` and its lux
+    /// counterparts.
+    pub fn is_sample_stale(&self) -> bool {
+        self.stale_wait_us != 0
+    }
+
+    /// Read ALS high resolution output data converted to lux
+    ///
+    /// For values higher than 1000 lx and 1/4 or 1/8 gain,
+    /// the following compensation formula is applied:
+    /// `lux = 6.0135e-13*(lux^4) - 9.3924e-9*(lux^3) + 8.1488e-5*(lux^2) + 1.0023*lux`
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_lux(&mut self) -> Result<Lux, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.convert_raw_als_to_lux(raw))
+    }
+
+    /// Calculate lux value for a raw ALS measurement.
+    ///
+    /// This takes into consideration the configured integration time and gain
+    /// and compensates the lux value if necessary.
+    ///
+    /// The selected correction model and calibration are applied. See
+    /// `set_correction_model()` and `set_calibration()`.
+    #[cfg(feature = "lux_as_f32")]
+    pub fn convert_raw_als_to_lux(&self, raw_als: u16) -> Lux {
+        let lux = raw_to_lux(self.correction, self.it, self.gain, raw_als);
+        Lux(self.calibration.apply(lux))
+    }
+
+    /// Read ALS high resolution output data converted to millilux
+    ///
+    /// This is the integer counterpart of `read_lux()` for targets without
+    /// a floating-point unit. See `convert_raw_als_to_millilux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn read_millilux(&mut self) -> Result<u32, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.convert_raw_als_to_millilux(raw))
+    }
+
+    /// Calculate millilux value for a raw ALS measurement.
+    ///
+    /// This takes into consideration the configured integration time and gain
+    /// and compensates the value if necessary.
+    ///
+    /// The selected correction model and calibration are applied. See
+    /// `set_correction_model()` and `set_calibration()`.
+    #[cfg(feature = "lux_as_u32")]
+    pub fn convert_raw_als_to_millilux(&self, raw_als: u16) -> u32 {
+        let millilux = raw_to_millilux(self.correction, self.it, self.gain, raw_als);
+        #[cfg(feature = "lux_as_f32")]
+        let millilux = self.calibration.apply_millilux(millilux);
+        millilux
+    }
+
+    /// Read white channel measurement
+    #[maybe_async]
+    pub async fn read_white(&mut self) -> Result<u16, Error<I2C::Error>> {
+        self.read_register(Register::WHITE).await
+    }
+
+    /// Read white channel measurement scaled with the white factor of the
+    /// calibration. See `set_calibration()`.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_white_calibrated(&mut self) -> Result<f32, Error<I2C::Error>> {
+        let raw = self.read_white().await?;
+        Ok(self.calibration.apply_white(raw))
+    }
+
+    #[maybe_async]
+    async fn read_register(&mut self, register: u8) -> Result<u16, Error<I2C::Error>> {
+        read_register(&mut self.i2c, self.address, register).await
+    }
+}
+
+impl<I2C> Veml7700<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Measure with automatic gain and integration time selection.
+    ///
+    /// This follows the flow of the "Designing the VEML7700 into an
+    /// application" note: starting at 1/8 gain and 100 ms, the gain and then
+    /// the integration time are increased while the ALS counts are 100 or
+    /// less. If the counts are above 10000 instead, the integration time is
+    /// decreased down to 25 ms.
+    ///
+    /// For every step the device is shut down, reconfigured and enabled again,
+    /// then a fresh measurement is waited for (see `read_raw_fresh()`), so
+    /// this can take a few seconds in the dark, more if the power-saving mode
+    /// is enabled.
+    ///
+    /// The device is left enabled with the gain and integration time the
+    /// measurement settled on.
+    #[maybe_async]
+    pub async fn measure_auto_range<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Measurement, Error<I2C::Error>> {
+        let mut gain = Gain::OneEighth;
+        let mut it = IntegrationTime::_100ms;
+        let mut raw = self.measure_with(gain, it, delay).await?;
+        if raw <= AUTO_RANGE_LOW_COUNTS {
+            while raw <= AUTO_RANGE_LOW_COUNTS {
+                if let Some(higher) = next_higher_gain(gain) {
+                    gain = higher;
+                } else if let Some(longer) = next_longer_integration_time(it) {
+                    it = longer;
+                } else {
+                    break;
+                }
+                raw = self.measure_with(gain, it, delay).await?;
+            }
+        } else {
+            while raw > AUTO_RANGE_HIGH_COUNTS {
+                match next_shorter_integration_time(it) {
+                    Some(shorter) => it = shorter,
+                    None => break,
+                }
+                raw = self.measure_with(gain, it, delay).await?;
+            }
+        }
+        Ok(self.measurement(raw))
+    }
+
+    /// Take a single measurement of both channels and shut the device down
+    /// again.
+    ///
+    /// This enables the device, waits for the 4 ms start-up time and a full
+    /// measurement with the configured settings (see `read_raw_fresh()`),
+    /// reads the ALS and white channels and disables the device.
+    /// This is useful for battery-powered applications measuring only once in
+    /// a while. The power-saving mode should be disabled, as its wait time
+    /// would be waited for as well.
+    ///
+    /// If reading fails, the device is still disabled before the error is
+    /// returned.
+    #[maybe_async]
+    pub async fn measure_once<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<OneShotMeasurement, Error<I2C::Error>> {
+        self.enable().await?;
+        let channels = self.read_both_channels(delay).await;
+        let disabled = self.disable().await;
+        let (raw, white) = channels?;
+        disabled?;
+        Ok(OneShotMeasurement {
+            als: self.measurement(raw),
+            white,
+        })
+    }
+
+    #[maybe_async]
+    async fn read_both_channels<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(u16, u16), Error<I2C::Error>> {
+        let raw = self.read_raw_fresh(delay).await?;
+        let white = self.read_white().await?;
+        Ok((raw, white))
+    }
+
+    #[maybe_async]
+    async fn measure_with<D: DelayNs>(
+        &mut self,
+        gain: Gain,
+        it: IntegrationTime,
+        delay: &mut D,
+    ) -> Result<u16, Error<I2C::Error>> {
+        let config = self
+            .config
+            .with_high(BitFlags::ALS_SD)
+            .with_gain(gain)
+            .with_integration_time(it);
+        self.rewrite_thresholds(it, gain).await?;
+        self.set_config(config).await?;
+        self.gain = gain;
+        self.it = it;
+        self.enable().await?;
+        self.read_raw_fresh(delay).await
+    }
+
+    /// Raw value of a threshold for the given settings and the current
+    /// correction model and calibration, if it can be represented.
+    fn raw_value(&self, threshold: Threshold, it: IntegrationTime, gain: Gain) -> Option<u16> {
+        #[cfg(feature = "lux_as_f32")]
+        let threshold = self.calibration.uncalibrated(threshold);
+        raw_threshold_value(threshold, self.correction, it, gain)
+    }
+
+    fn measurement(&self, raw: u16) -> Measurement {
+        let status = range_status(raw);
+        Measurement {
+            raw,
+            gain: self.gain,
+            integration_time: self.it,
+            status,
+            adjustment: range_adjustment(status, self.gain, self.it),
+            #[cfg(feature = "lux_as_f32")]
+            lux: self.convert_raw_als_to_lux(raw),
+            #[cfg(feature = "lux_as_u32")]
+            millilux: self.convert_raw_als_to_millilux(raw),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Register access shared by the device drivers.
+
+use super::{maybe_async, I2c, SevenBitAddress};
+use crate::Error;
+
+/// Write a 16-bit register, least significant byte first.
+#[maybe_async]
+pub(crate) async fn write_register<I2C>(
+    i2c: &mut I2C,
+    address: u8,
+    register: u8,
+    value: u16,
+) -> Result<(), Error<I2C::Error>>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    i2c.write(address, &[register, value as u8, (value >> 8) as u8])
+        .await
+        .map_err(Error::I2C)
+}
+
+/// Read a 16-bit register, least significant byte first.
+#[maybe_async]
+pub(crate) async fn read_register<I2C>(
+    i2c: &mut I2C,
+    address: u8,
+    register: u8,
+) -> Result<u16, Error<I2C::Error>>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    let mut data = [0; 2];
+    i2c.write_read(address, &[register], &mut data)
+        .await
+        .map_err(Error::I2C)
+        .and(Ok(u16::from(data[0]) | u16::from(data[1]) << 8))
+}
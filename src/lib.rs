@@ -1,36 +1,71 @@
-//! This is a platform agnostic Rust driver for the Veml7700 and VEML7700 high-accuracy
+//! This is a platform agnostic Rust driver for the VEML7700 and VEML6030 high-accuracy
 //! ambient light sensors using the [`embedded-hal`] traits.
 //!
 //! [`embedded-hal`]: https://github.com/rust-embedded/embedded-hal
 //!
 //! This driver allows you to:
 //! - Enable/disable the device. See: [`enable()`].
+//! - Take over the configuration the device already has. See: [`sync_from_device()`].
+//! - Check that the device is present and is a VEML7700 or VEML6030. See: [`probe()`].
+//! - Get the current configuration. See: [`settings()`].
+//! - Apply a complete configuration at once. See: [`apply()`].
 //! - Read the measured lux value. See: [`read_lux()`].
+//! - Convert lux to and from foot-candles. See: [`Lux`].
+//! - Read the measured lux value without floating point. See: [`read_millilux()`].
 //! - Read the white channel measurement. See: [`read_white()`].
 //! - Read the measured ALS value in raw format. See: [`read_raw()`].
+//! - Wait for a measurement taken with the current settings. See: [`read_raw_fresh()`].
+//! - Detect saturated and under-range measurements. See: [`read_measurement()`].
+//! - Fail on saturated measurements. See: [`read_measurement_strict()`].
 //! - Calculate the compensated lux for a raw ALS value. See: [`convert_raw_als_to_lux()`].
+//! - Select the non-linearity correction. See: [`set_correction_model()`].
+//! - Compensate for a window or cover glass. See: [`set_calibration()`].
+//! - Calibrate against a reference lux meter. See the [`calibration`] module.
 //! - Set the gain. See: [`set_gain()`].
 //! - Set the integration time. See: [`set_integration_time()`].
 //! - Set the fault count. See: [`set_fault_count()`].
 //! - Enable/disable and configure power saving mode. See: [`enable_power_saving()`].
 //! - Enable/disable interrupts. See: [`enable_interrupts()`].
 //! - Read the interrupt status. See: [`read_interrupt_status()`].
-//! - Set the high/low thresholds in lux or raw. See: [`set_high_threshold_lux()`].
+//! - Wait for an interrupt on the INT pin. See: [`wait_for_interrupt()`].
+//! - Set the high/low thresholds in lux or raw. Thresholds in lux follow gain and integration time changes. See: [`set_high_threshold_lux()`].
+//! - Set both thresholds at once, checking their order. See: [`set_thresholds_lux()`].
+//! - Interrupt whenever the light level changes by a given amount. See: [`enable_interrupt_on_change()`].
 //! - Calculate the compensated raw threshold value ahead of time. See: [`calculate_raw_threshold_value()`].
+//! - Calculate the raw threshold value at compile time. See: [`calculate_raw_threshold_value_millilux()`].
+//! - Measure with automatic gain and integration time selection. See: [`measure_auto_range()`].
+//! - Take a single measurement and shut the device down again. See: [`measure_once()`].
 //!
-//! [`enable()`]: struct.Veml7700.html#method.enable
-//! [`read_lux()`]: struct.Veml7700.html#method.read_lux
-//! [`read_white()`]: struct.Veml7700.html#method.read_white
-//! [`read_raw()`]: struct.Veml7700.html#method.read_raw
+//! [`enable()`]: blocking/struct.Veml7700.html#method.enable
+//! [`sync_from_device()`]: blocking/struct.Veml7700.html#method.sync_from_device
+//! [`probe()`]: blocking/struct.Veml7700.html#method.probe
+//! [`settings()`]: blocking/struct.Veml7700.html#method.settings
+//! [`apply()`]: blocking/struct.Veml7700.html#method.apply
+//! [`read_lux()`]: blocking/struct.Veml7700.html#method.read_lux
+//! [`Lux`]: struct.Lux.html
+//! [`read_millilux()`]: blocking/struct.Veml7700.html#method.read_millilux
+//! [`read_white()`]: blocking/struct.Veml7700.html#method.read_white
+//! [`read_raw()`]: blocking/struct.Veml7700.html#method.read_raw
+//! [`read_raw_fresh()`]: blocking/struct.Veml7700.html#method.read_raw_fresh
+//! [`read_measurement()`]: blocking/struct.Veml7700.html#method.read_measurement
+//! [`read_measurement_strict()`]: blocking/struct.Veml7700.html#method.read_measurement_strict
 //! [`convert_raw_als_to_lux()`]: fn.convert_raw_als_to_lux.html
-//! [`set_gain()`]: struct.Veml7700.html#method.set_gain
-//! [`set_integration_time()`]: struct.Veml7700.html#method.set_integration_time
-//! [`set_fault_count()`]: struct.Veml7700.html#method.set_fault_count
-//! [`enable_power_saving()`]: struct.Veml7700.html#method.enable_power_saving
-//! [`enable_interrupts()`]: struct.Veml7700.html#method.enable_interrupts
-//! [`read_interrupt_status()`]: struct.Veml7700.html#method.read_interrupt_status
-//! [`set_high_threshold_lux()`]: struct.Veml7700.html#method.set_high_threshold_lux
+//! [`set_correction_model()`]: blocking/struct.Veml7700.html#method.set_correction_model
+//! [`set_calibration()`]: blocking/struct.Veml7700.html#method.set_calibration
+//! [`set_gain()`]: blocking/struct.Veml7700.html#method.set_gain
+//! [`set_integration_time()`]: blocking/struct.Veml7700.html#method.set_integration_time
+//! [`set_fault_count()`]: blocking/struct.Veml7700.html#method.set_fault_count
+//! [`enable_power_saving()`]: blocking/struct.Veml7700.html#method.enable_power_saving
+//! [`enable_interrupts()`]: blocking/struct.Veml7700.html#method.enable_interrupts
+//! [`read_interrupt_status()`]: blocking/struct.Veml7700.html#method.read_interrupt_status
+//! [`wait_for_interrupt()`]: blocking/struct.Veml7700.html#method.wait_for_interrupt
+//! [`set_high_threshold_lux()`]: blocking/struct.Veml7700.html#method.set_high_threshold_lux
+//! [`set_thresholds_lux()`]: blocking/struct.Veml7700.html#method.set_thresholds_lux
+//! [`enable_interrupt_on_change()`]: blocking/struct.Veml7700.html#method.enable_interrupt_on_change
 //! [`calculate_raw_threshold_value()`]: fn.calculate_raw_threshold_value.html
+//! [`calculate_raw_threshold_value_millilux()`]: fn.calculate_raw_threshold_value_millilux.html
+//! [`measure_auto_range()`]: blocking/struct.Veml7700.html#method.measure_auto_range
+//! [`measure_once()`]: blocking/struct.Veml7700.html#method.measure_once
 //!
 //! ## The device
 //!
@@ -43,6 +78,23 @@
 //!
 //! Datasheet: [VEML7700](https://www.vishay.com/docs/84286/veml7700.pdf)
 //!
+//! The VEML6030 has the same register map and resolution but an ADDR pin
+//! selecting the slave address 0x10 or 0x48, so two of them can share a bus.
+//! It is driven by the same driver, available as `Veml6030` as well.
+//! See [`new_with_address()`].
+//!
+//! Datasheet: [VEML6030](https://www.vishay.com/docs/84366/veml6030.pdf)
+//!
+//! [`new_with_address()`]: blocking/struct.Veml7700.html#method.new_with_address
+//!
+//! The VEML6035 is a low-power sensor with the same measurement, threshold
+//! and interrupt registers but a different configuration register. It is
+//! driven by the separate `Veml6035` driver. See the [`veml6035`] module.
+//!
+//! The VEML3235 has 16-bit ALS and white channels like the VEML7700 but its
+//! own register map and no thresholds or interrupt. It is driven by the
+//! separate `Veml3235` driver. See the [`veml3235`] module.
+//!
 //! Application Note:
 //! - [Designing the VEML7700 into an application](https://www.vishay.com/docs/84323/designingveml7700.pdf)
 //!
@@ -52,44 +104,138 @@
 //! then instantiate the appropriate device.
 //!
 //! VEML7700 expose interface over I2C.
+//!
+//! The driver is available in a blocking version using the [`embedded-hal`]
+//! traits in the [`blocking`] module and in an async version using the
+//! [`embedded-hal-async`] traits in the [`asynch`] module. Both share the same
+//! API apart from the async methods and can be used in the same build.
+//! The blocking driver is also re-exported at the crate root.
+//!
+//! Both modules also offer a version of the driver tracking whether the device
+//! is enabled in its type, so that reading a device that is still shut down
+//! does not compile. See [`blocking::typestate`].
+//!
+//! [`embedded-hal-async`]: https://docs.rs/embedded-hal-async
+//!
+//! ## Features
+//!
+//! - `lux_as_f32` (default): lux conversions in `f32`.
+//! - `lux_as_u32`: lux conversions in integer millilux, for targets without a
+//!   floating-point unit. Both features can be enabled at the same time.
+//! - `uom`: conversions between `Lux` and the illuminance quantity of
+//!   [`uom`](https://docs.rs/uom). See `Illuminance`. Implies `lux_as_f32`.
+//! - `sim`: simulated device implementing the I²C traits, for running the
+//!   driver on a host. See the `sim` module.
 #![deny(unsafe_code, missing_docs)]
 #![no_std]
 
+#[cfg(feature = "lux_as_f32")]
+pub mod calibration;
 #[cfg(feature = "lux_as_f32")]
 mod correction;
 
+pub mod asynch;
+pub mod blocking;
 mod device_impl;
+mod millilux;
+#[cfg(any(feature = "sim", test))]
+pub mod sim;
+#[cfg(test)]
+mod tests;
 mod types;
+pub mod veml3235;
+pub mod veml6035;
 
 #[cfg(feature = "lux_as_f32")]
 pub use crate::correction::calculate_raw_threshold_value;
 #[cfg(feature = "lux_as_f32")]
 pub use crate::device_impl::convert_raw_als_to_lux;
+#[cfg(feature = "lux_as_u32")]
+pub use crate::device_impl::convert_raw_als_to_millilux;
+pub use crate::millilux::calculate_raw_threshold_value_millilux;
 
-pub use crate::types::{FaultCount, Gain, IntegrationTime, InterruptStatus, PowerSavingMode};
+/// The blocking driver, also available as [`blocking::Veml6030`].
+pub use crate::blocking::Veml6030;
+/// The blocking driver, also available as [`blocking::Veml7700`].
+pub use crate::blocking::Veml7700;
+#[cfg(feature = "uom")]
+pub use crate::types::Illuminance;
+#[cfg(feature = "lux_as_f32")]
+pub use crate::types::{Calibration, Lux};
+pub use crate::types::{
+    ChangeWindow, CorrectionModel, DeviceId, FaultCount, Gain, IntegrationTime, InterruptEvent,
+    InterruptStatus, Measurement, OneShotMeasurement, PowerSavingMode, RangeAdjustment,
+    RangeStatus, Settings, SlaveAddr, Threshold, Veml7700Config,
+};
 
 /// All possible errors in this crate
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error<E> {
     /// I²C bus error
     I2C(E),
+    /// A threshold cannot be represented with the current gain and
+    /// integration time (negative, not a number or above the 16-bit range),
+    /// or the low threshold is above the high threshold.
+    InvalidThreshold,
+    /// The ALS channel is at or close to the end of its 16-bit range, so the
+    /// measurement may not reflect the real illuminance. Returned by
+    /// `read_measurement_strict()`.
+    Saturated,
+    /// The device is shut down, so there is no measurement to start from.
+    NotReady,
+    /// The device did not acknowledge its address.
+    DeviceNotFound,
+    /// The device answered with an unexpected ID, contained here.
+    WrongId(u16),
+    /// An operation did not complete in time.
+    Timeout,
+    /// Error reading the pin connected to the INT output.
+    Pin(embedded_hal::digital::ErrorKind),
+    /// A calibration factor is not positive and finite, or the offset is not
+    /// finite.
+    InvalidCalibration,
+    /// The coefficients of a custom correction polynomial are not finite or
+    /// it is not strictly increasing over the range of the sensor.
+    InvalidCorrectionModel,
 }
+
 impl<E> From<E> for Error<E> {
     fn from(other: E) -> Self {
         Error::I2C(other)
     }
 }
 
-const DEVICE_ADDRESS: u8 = 0x10;
+impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::I2C(e) => write!(f, "I2C bus error: {e:?}"),
+            Error::InvalidThreshold => f.write_str("invalid threshold"),
+            Error::Saturated => f.write_str("ALS channel saturated"),
+            Error::NotReady => f.write_str("device is shut down"),
+            Error::DeviceNotFound => f.write_str("device not found"),
+            Error::WrongId(id) => write!(f, "unexpected device ID {id:#06x}"),
+            Error::Timeout => f.write_str("timeout"),
+            Error::Pin(kind) => write!(f, "INT pin error: {kind:?}"),
+            Error::InvalidCalibration => f.write_str("invalid calibration"),
+            Error::InvalidCorrectionModel => f.write_str("invalid correction model"),
+        }
+    }
+}
 
-/// VEML7700 device driver.
-#[derive(Debug)]
-pub struct Veml7700<I2C> {
-    /// The concrete I²C device implementation.
-    i2c: I2C,
-    config: Config,
-    gain: Gain,
-    it: IntegrationTime,
+impl<E: core::fmt::Debug> core::error::Error for Error<E> {}
+
+/// Bus errors keep their kind, so the driver error can be passed on
+/// wherever an I²C error is expected.
+impl<E: embedded_hal::i2c::Error> embedded_hal::i2c::Error for Error<E> {
+    fn kind(&self) -> embedded_hal::i2c::ErrorKind {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+        match self {
+            Error::I2C(e) => e.kind(),
+            Error::DeviceNotFound => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+            _ => ErrorKind::Other,
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
@@ -0,0 +1,213 @@
+#[cfg(feature = "lux_as_u32")]
+use crate::CorrectionModel;
+use crate::{Gain, IntegrationTime};
+
+/// Calculate raw value for threshold in millilux applying compensation if necessary.
+///
+/// This is the integer counterpart of `calculate_raw_threshold_value()`.
+///
+/// For values higher than 1000 lx and 1/4 or 1/8 gain, the inverse of the
+/// compensation formula is found through a binary search over the integer
+/// version of the polynomial. The inverse is exact to 1 millilux, so the
+/// result is the raw value whose converted value is closest to `millilux`
+/// without exceeding it.
+///
+/// Values above the range of the ALS register saturate to `u16::MAX`. The
+/// driver methods setting thresholds in millilux return
+/// `Error::InvalidThreshold` for these instead.
+///
+/// This uses the default `CorrectionModel::LowGain`. It is a `const fn` and
+/// does not need any feature, so threshold tables can be calculated at
+/// compile time:
+///
+/// ```
+/// use veml7700::{calculate_raw_threshold_value_millilux, Gain, IntegrationTime};
+///
+/// const HIGH_THRESHOLD: u16 =
+///     calculate_raw_threshold_value_millilux(IntegrationTime::_100ms, Gain::OneEighth, 2_000_000);
+/// ```
+pub const fn calculate_raw_threshold_value_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    millilux: u32,
+) -> u16 {
+    match checked_raw_threshold_value_millilux(it, gain, millilux) {
+        Some(raw) => raw,
+        None => u16::MAX,
+    }
+}
+
+/// Like `calculate_raw_threshold_value_millilux()` but return `None` for
+/// values above the range of the ALS register.
+pub(crate) const fn checked_raw_threshold_value_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    millilux: u32,
+) -> Option<u16> {
+    let millilux = if matches!(gain, Gain::OneQuarter | Gain::OneEighth) && millilux > 1_000_000 {
+        if millilux as i128 > correct_high_millilux_wide(MAX_MILLILUX) {
+            return None;
+        }
+        inverse_high_millilux_correction(millilux)
+    } else {
+        millilux
+    };
+    uncorrected_raw_threshold_value_millilux(it, gain, millilux)
+}
+
+/// Like `checked_raw_threshold_value_millilux()` but with the given
+/// correction model.
+#[cfg(feature = "lux_as_u32")]
+pub(crate) fn checked_raw_threshold_value_millilux_with_model(
+    model: CorrectionModel,
+    it: IntegrationTime,
+    gain: Gain,
+    millilux: u32,
+) -> Option<u16> {
+    match model {
+        CorrectionModel::None => uncorrected_raw_threshold_value_millilux(it, gain, millilux),
+        CorrectionModel::LowGain => checked_raw_threshold_value_millilux(it, gain, millilux),
+        CorrectionModel::AllSettings => {
+            if millilux as i128 > correct_high_millilux_wide(MAX_MILLILUX) {
+                return None;
+            }
+            let millilux = inverse_high_millilux_correction(millilux);
+            uncorrected_raw_threshold_value_millilux(it, gain, millilux)
+        }
+        #[cfg(feature = "lux_as_f32")]
+        CorrectionModel::Custom(_) => crate::correction::checked_raw_threshold_value(
+            model,
+            it,
+            gain,
+            millilux as f32 / 1000.0,
+        ),
+    }
+}
+
+const fn uncorrected_raw_threshold_value_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    millilux: u32,
+) -> Option<u16> {
+    let raw = millilux as u64 * 10 / get_millilux_raw_conversion_divisor(it, gain);
+    if raw > u16::MAX as u64 {
+        None
+    } else {
+        Some(raw as u16)
+    }
+}
+
+/// Apply the non-linearity correction of `model` to an uncorrected millilux
+/// value.
+///
+/// Custom coefficients are evaluated in `f32`.
+#[cfg(feature = "lux_as_u32")]
+pub(crate) fn correct_millilux(model: CorrectionModel, gain: Gain, millilux: u32) -> u32 {
+    match model {
+        CorrectionModel::None => millilux,
+        CorrectionModel::LowGain => {
+            if (gain == Gain::OneQuarter || gain == Gain::OneEighth) && millilux > 1_000_000 {
+                correct_high_millilux(millilux)
+            } else {
+                millilux
+            }
+        }
+        CorrectionModel::AllSettings => correct_high_millilux(millilux),
+        #[cfg(feature = "lux_as_f32")]
+        CorrectionModel::Custom(_) => {
+            let lux = crate::correction::correct_lux(model, gain, millilux as f32 / 1000.0);
+            // This saturates.
+            (lux * 1000.0) as u32
+        }
+    }
+}
+
+/// Multiply a raw ALS value by the lux/count resolution, in millilux.
+///
+/// The resolution is 3.6 mlx/count at gain 2 and 800 ms and doubles with each
+/// halving of the gain or the integration time, so this is exact apart from
+/// the final truncation.
+#[cfg(feature = "lux_as_u32")]
+pub(crate) fn convert_raw_to_millilux(it: IntegrationTime, gain: Gain, raw: u16) -> u32 {
+    let millilux = u64::from(raw) * get_millilux_raw_conversion_divisor(it, gain) / 10;
+    millilux as u32
+}
+
+/// Resolution in tenths of millilux per count.
+pub(crate) const fn get_millilux_raw_conversion_divisor(it: IntegrationTime, gain: Gain) -> u64 {
+    let gain_factor = match gain {
+        Gain::Two => 1,
+        Gain::One => 2,
+        Gain::OneQuarter => 8,
+        Gain::OneEighth => 16,
+    };
+    (36 * gain_factor) << integration_time_shift(it)
+}
+
+/// Number of halvings of the integration time from 800 ms.
+pub(crate) const fn integration_time_shift(it: IntegrationTime) -> u32 {
+    match it {
+        IntegrationTime::_800ms => 0,
+        IntegrationTime::_400ms => 1,
+        IntegrationTime::_200ms => 2,
+        IntegrationTime::_100ms => 3,
+        IntegrationTime::_50ms => 4,
+        IntegrationTime::_25ms => 5,
+    }
+}
+
+/// Highest value the sensor can produce: full scale at 1/8 gain and 25 ms.
+const MAX_MILLILUX: u32 = 65535 * 36 * 16 * 32 / 10;
+
+/// Integer version of
+/// `lux = 6.0135e-13*(lux^4) - 9.3924e-9*(lux^3) + 8.1488e-5*(lux^2) + 1.0023*lux`.
+///
+/// With `m` in millilux this is
+/// `(60135*m^4 - 93924e7*m^3 + 81488e14*m^2 + 10023e22*m) / 1e26`, which fits
+/// in an `i128` for every millilux value the sensor can produce. Results that
+/// do not fit in a `u32` saturate, which happens above roughly 52000 lx.
+#[cfg(feature = "lux_as_u32")]
+fn correct_high_millilux(millilux: u32) -> u32 {
+    let corrected = correct_high_millilux_wide(millilux);
+    if corrected > i128::from(u32::MAX) {
+        u32::MAX
+    } else {
+        corrected as u32
+    }
+}
+
+const fn correct_high_millilux_wide(millilux: u32) -> i128 {
+    const C0: i128 = 10023 * 10_i128.pow(22);
+    const C1: i128 = 81488 * 10_i128.pow(14);
+    const C2: i128 = -93924 * 10_i128.pow(7);
+    const C3: i128 = 60135;
+    let m = if millilux > MAX_MILLILUX {
+        MAX_MILLILUX
+    } else {
+        millilux
+    } as i128;
+    m * (C0 + m * (C1 + m * (C2 + m * C3))) / 10_i128.pow(26)
+}
+
+/// Largest millilux value whose corrected value does not exceed `millilux`.
+///
+/// The correction polynomial is strictly increasing and never smaller than
+/// its input, so the result lies in `0..=millilux`.
+pub(crate) const fn inverse_high_millilux_correction(millilux: u32) -> u32 {
+    let target = millilux as i128;
+    let mut low = 0;
+    let mut high = if millilux > MAX_MILLILUX {
+        MAX_MILLILUX
+    } else {
+        millilux
+    };
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if correct_high_millilux_wide(mid) <= target {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
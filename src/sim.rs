@@ -0,0 +1,345 @@
+//! Simulated VEML7700 for running the driver on a host.
+//!
+//! [`SimulatedVeml7700`] models the register map of the device, the
+//! integration cycle timing, the gain and integration time scaling including
+//! the 16-bit saturation, the fault count (persistence) logic of the
+//! interrupts and the clearing of the interrupt flags on read.
+//!
+//! The simulated time only advances through [`SimulatedVeml7700::advance_us()`]
+//! or through the [`DelayNs`] implementation, so the driver behaves exactly as
+//! it would on a real device without any actual waiting.
+//!
+//! The I²C and delay traits (both blocking and async) are implemented for
+//! `&SimulatedVeml7700`, so the same simulated device can be handed to the
+//! driver and used as delay source at the same time:
+//!
+//! ```
+//! # #[cfg(feature = "lux_as_f32")]
+//! # {
+//! use embedded_hal::delay::DelayNs;
+//! use veml7700::{blocking::Veml7700, sim::SimulatedVeml7700};
+//!
+//! let sim = SimulatedVeml7700::new();
+//! sim.set_lux(250.0);
+//! let mut sensor = Veml7700::new(&sim);
+//! sensor.enable().unwrap();
+//! let mut delay = &sim;
+//! delay.delay_ms(120);
+//! let lux = sensor.read_lux().unwrap();
+//! assert!((lux.lux() - 250.0).abs() < 0.1);
+//! # }
+//! ```
+//!
+//! [`DelayNs`]: embedded_hal::delay::DelayNs
+
+use crate::device_impl::{
+    decode_power_saving_mode, BitFlags, Register, DEVICE_ID, STARTUP_TIME_US,
+};
+use crate::millilux::{get_millilux_raw_conversion_divisor, inverse_high_millilux_correction};
+use crate::{Config, Gain, IntegrationTime, SlaveAddr};
+use core::cell::RefCell;
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource, Operation, SevenBitAddress};
+
+/// Errors reported by the simulated device
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Error {
+    /// A transaction was addressed to a different device.
+    NoAcknowledge,
+    /// A register that does not exist or is not writable was accessed.
+    InvalidRegister(u8),
+    /// A transfer did not have the length of a register access.
+    InvalidLength,
+}
+
+impl embedded_hal::i2c::Error for Error {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::NoAcknowledge => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+            Error::InvalidRegister(_) | Error::InvalidLength => ErrorKind::Other,
+        }
+    }
+}
+
+/// Simulated VEML7700 device.
+///
+/// Measurements start 4 ms after the device is enabled and complete every
+/// integration time, plus the power-saving mode wait time if enabled. The
+/// gain and integration time are latched at the start of each measurement,
+/// so the ALS and WHITE registers keep results taken with the previous
+/// settings until a full measurement with the new settings has completed.
+///
+/// At 1/4 and 1/8 gain the ALS response above 1000 lx follows the inverse of
+/// the compensation formula the driver applies, so the driver reads back the
+/// configured illuminance.
+#[derive(Debug)]
+pub struct SimulatedVeml7700 {
+    address: SlaveAddr,
+    state: RefCell<State>,
+}
+
+impl Default for SimulatedVeml7700 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    als_conf: u16,
+    als_wh: u16,
+    als_wl: u16,
+    psm: u16,
+    als: u16,
+    white: u16,
+    als_int: u16,
+    id: u16,
+    pointer: u8,
+    now_us: u64,
+    /// Start of the running measurement and the configuration latched for it.
+    measurement: Option<(u64, Config)>,
+    high_count: u8,
+    low_count: u8,
+    millilux: u32,
+    white_millilux: u32,
+}
+
+impl SimulatedVeml7700 {
+    /// Create a new simulated device in its power-on state (shut down,
+    /// gain 1, 100 ms integration time) in complete darkness.
+    pub fn new() -> Self {
+        Self::new_with_address(SlaveAddr::default())
+    }
+
+    /// Create a new simulated device answering at the given slave address,
+    /// like a VEML6030 with its ADDR pin set accordingly.
+    pub fn new_with_address(address: SlaveAddr) -> Self {
+        SimulatedVeml7700 {
+            address,
+            state: RefCell::new(State {
+                als_conf: BitFlags::ALS_SD,
+                id: u16::from(address.address_option()) << 8 | u16::from(DEVICE_ID),
+                ..State::default()
+            }),
+        }
+    }
+
+    /// Set the illuminance seen by both the ALS and the white channel.
+    pub fn set_lux(&self, lux: f32) {
+        let mut state = self.state.borrow_mut();
+        state.millilux = to_millilux(lux);
+        state.white_millilux = state.millilux;
+    }
+
+    /// Set the illuminance seen by the white channel only.
+    pub fn set_white_lux(&self, lux: f32) {
+        self.state.borrow_mut().white_millilux = to_millilux(lux);
+    }
+
+    /// Set the contents of the ID register, for simulating a different part.
+    pub fn set_device_id(&self, id: u16) {
+        self.state.borrow_mut().id = id;
+    }
+
+    /// Advance the simulated time, completing any measurements that end
+    /// in this period.
+    pub fn advance_us(&self, us: u32) {
+        self.state.borrow_mut().advance_to_us(u64::from(us));
+    }
+
+    /// Return the simulated time elapsed since creation in microseconds.
+    pub fn now_us(&self) -> u64 {
+        self.state.borrow().now_us
+    }
+
+    /// Return whether the INT output is asserted (driven low).
+    pub fn is_interrupt_asserted(&self) -> bool {
+        let state = self.state.borrow();
+        state.als_conf & BitFlags::ALS_INT_EN != 0
+            && state.als_int & (BitFlags::INT_TH_LOW | BitFlags::INT_TH_HIGH) != 0
+    }
+
+    /// Return the current value of a register without side effects.
+    pub fn peek_register(&self, register: u8) -> Option<u16> {
+        self.state.borrow().register(register)
+    }
+
+    fn transaction(
+        &self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Error> {
+        if address != self.address.addr() {
+            return Err(Error::NoAcknowledge);
+        }
+        let mut state = self.state.borrow_mut();
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => match **bytes {
+                    [register] => state.pointer = register,
+                    [register, low, high] => {
+                        state.pointer = register;
+                        state.write(register, u16::from_le_bytes([low, high]))?;
+                    }
+                    _ => return Err(Error::InvalidLength),
+                },
+                Operation::Read(buffer) if buffer.len() == 2 => {
+                    let register = state.pointer;
+                    let value = state.read(register)?;
+                    buffer.copy_from_slice(&value.to_le_bytes());
+                }
+                Operation::Read(_) => return Err(Error::InvalidLength),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl State {
+    fn register(&self, register: u8) -> Option<u16> {
+        match register {
+            Register::ALS_CONF => Some(self.als_conf),
+            Register::ALS_WH => Some(self.als_wh),
+            Register::ALS_WL => Some(self.als_wl),
+            Register::PSM => Some(self.psm),
+            Register::ALS => Some(self.als),
+            Register::WHITE => Some(self.white),
+            Register::ALS_INT => Some(self.als_int),
+            Register::ID => Some(self.id),
+            _ => None,
+        }
+    }
+
+    fn read(&mut self, register: u8) -> Result<u16, Error> {
+        let value = self
+            .register(register)
+            .ok_or(Error::InvalidRegister(register))?;
+        if register == Register::ALS_INT {
+            self.als_int = 0;
+        }
+        Ok(value)
+    }
+
+    fn write(&mut self, register: u8, value: u16) -> Result<(), Error> {
+        match register {
+            Register::ALS_CONF => {
+                let was_enabled = self.als_conf & BitFlags::ALS_SD == 0;
+                self.als_conf = value;
+                match (was_enabled, value & BitFlags::ALS_SD == 0) {
+                    (false, true) => {
+                        let start_us = self.now_us + u64::from(STARTUP_TIME_US);
+                        self.measurement = Some((start_us, Config { bits: value }));
+                    }
+                    (true, false) => self.measurement = None,
+                    _ => (),
+                }
+            }
+            Register::ALS_WH => self.als_wh = value,
+            Register::ALS_WL => self.als_wl = value,
+            Register::PSM => self.psm = value,
+            _ => return Err(Error::InvalidRegister(register)),
+        }
+        Ok(())
+    }
+
+    fn advance_to_us(&mut self, us: u64) {
+        let end_us = self.now_us + us;
+        while let Some((start_us, config)) = self.measurement {
+            let it = config.integration_time();
+            let done_us = start_us + u64::from(it.as_us());
+            if done_us > end_us {
+                break;
+            }
+            self.now_us = done_us;
+            self.complete_measurement(config);
+            let next_start_us = done_us + self.power_saving_wait_us();
+            let config = Config {
+                bits: self.als_conf,
+            };
+            self.measurement = Some((next_start_us, config));
+        }
+        self.now_us = end_us;
+    }
+
+    fn complete_measurement(&mut self, config: Config) {
+        let gain = config.gain();
+        let it = config.integration_time();
+        let mut als_millilux = self.millilux;
+        if (gain == Gain::OneQuarter || gain == Gain::OneEighth) && als_millilux > 1_000_000 {
+            als_millilux = inverse_high_millilux_correction(als_millilux);
+        }
+        self.als = to_counts(it, gain, als_millilux);
+        self.white = to_counts(it, gain, self.white_millilux);
+
+        if self.als_conf & BitFlags::ALS_INT_EN == 0 {
+            return;
+        }
+        // The fault count field encodes 1, 2, 4 and 8 consecutive faults.
+        let persistence = 1 << ((self.als_conf >> 4) & 0b11);
+        self.high_count = if self.als > self.als_wh {
+            self.high_count.saturating_add(1)
+        } else {
+            0
+        };
+        self.low_count = if self.als < self.als_wl {
+            self.low_count.saturating_add(1)
+        } else {
+            0
+        };
+        if self.high_count >= persistence {
+            self.als_int |= BitFlags::INT_TH_HIGH;
+        }
+        if self.low_count >= persistence {
+            self.als_int |= BitFlags::INT_TH_LOW;
+        }
+    }
+
+    fn power_saving_wait_us(&self) -> u64 {
+        decode_power_saving_mode(self.psm).map_or(0, |psm| u64::from(psm.wait_time_ms()) * 1000)
+    }
+}
+
+fn to_millilux(lux: f32) -> u32 {
+    // Float to integer casts saturate and map negative values to zero.
+    (lux * 1000.0) as u32
+}
+
+fn to_counts(it: IntegrationTime, gain: Gain, millilux: u32) -> u16 {
+    let counts = u64::from(millilux) * 10 / get_millilux_raw_conversion_divisor(it, gain);
+    counts.min(u64::from(u16::MAX)) as u16
+}
+
+impl embedded_hal::i2c::ErrorType for &SimulatedVeml7700 {
+    type Error = Error;
+}
+
+impl embedded_hal::i2c::I2c for &SimulatedVeml7700 {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        SimulatedVeml7700::transaction(self, address, operations)
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for &SimulatedVeml7700 {
+    async fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        SimulatedVeml7700::transaction(self, address, operations)
+    }
+}
+
+impl embedded_hal::delay::DelayNs for &SimulatedVeml7700 {
+    fn delay_ns(&mut self, ns: u32) {
+        self.advance_us(ns.div_ceil(1000));
+    }
+}
+
+impl embedded_hal_async::delay::DelayNs for &SimulatedVeml7700 {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.advance_us(ns.div_ceil(1000));
+    }
+}
@@ -0,0 +1,107 @@
+extern crate std;
+
+use super::is_shut_down;
+use crate::asynch::Veml7700;
+use crate::sim::SimulatedVeml7700;
+use crate::ChangeWindow;
+use core::convert::Infallible;
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use embedded_hal::digital::ErrorType;
+use embedded_hal_async::digital::Wait;
+use std::sync::Arc;
+use std::task::Wake;
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Poll `future` to completion. The simulated device never makes it wait,
+/// so it does not need to be woken.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// INT pin of the simulated device, which must already be asserted when
+/// waiting for it.
+struct IntPin<'a>(&'a SimulatedVeml7700);
+
+impl ErrorType for IntPin<'_> {
+    type Error = Infallible;
+}
+
+impl Wait for IntPin<'_> {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        assert!(!self.0.is_interrupt_asserted());
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        assert!(self.0.is_interrupt_asserted());
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn reads_a_fresh_measurement() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = Veml7700::new(&sim);
+    block_on(async {
+        device.enable().await.unwrap();
+        assert_eq!(device.read_raw_fresh(&mut &sim).await.unwrap(), 1736);
+    });
+}
+
+#[test]
+fn measures_once() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = Veml7700::new(&sim);
+    let measurement = block_on(device.measure_once(&mut &sim)).unwrap();
+    assert_eq!(measurement.als.raw, 1736);
+    assert!(is_shut_down(&sim));
+}
+
+#[test]
+fn waits_for_an_interrupt() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = Veml7700::new(&sim);
+    let event = block_on(async {
+        device.enable().await.unwrap();
+        device.read_raw_fresh(&mut &sim).await.unwrap();
+        device
+            .enable_interrupt_on_change(ChangeWindow::Raw(100))
+            .await
+            .unwrap();
+        sim.set_lux(200.0);
+        sim.advance_us(100_000);
+        device.wait_for_interrupt(&mut IntPin(&sim)).await.unwrap()
+    });
+    assert!(event.status.was_too_high);
+    assert_eq!(event.measurement.raw, 3472);
+    assert!(!sim.is_interrupt_asserted());
+}
@@ -0,0 +1,44 @@
+use crate::blocking::Veml7700;
+use crate::sim::SimulatedVeml7700;
+use crate::{Gain, IntegrationTime, RangeStatus};
+
+#[test]
+fn increases_sensitivity_in_the_dark() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(1.0);
+    let mut device = Veml7700::new(&sim);
+    let measurement = device.measure_auto_range(&mut &sim).unwrap();
+    // 1 lx gives 34 counts at gain 2 and 100 ms and 138 counts at 400 ms.
+    assert_eq!(measurement.gain, Gain::Two);
+    assert_eq!(measurement.integration_time, IntegrationTime::_400ms);
+    assert_eq!(measurement.raw, 138);
+    assert_eq!(measurement.status, RangeStatus::InRange);
+    assert_eq!(device.settings().gain, Gain::Two);
+    assert!(device.settings().enabled);
+}
+
+#[test]
+fn keeps_the_start_settings_in_range() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = Veml7700::new(&sim);
+    let measurement = device.measure_auto_range(&mut &sim).unwrap();
+    assert_eq!(measurement.gain, Gain::OneEighth);
+    assert_eq!(measurement.integration_time, IntegrationTime::_100ms);
+    assert_eq!(measurement.raw, 217);
+}
+
+#[test]
+fn decreases_integration_time_in_bright_light() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(50000.0);
+    let mut device = Veml7700::new(&sim);
+    let measurement = device.measure_auto_range(&mut &sim).unwrap();
+    // Still above 10000 counts at 25 ms, which is as short as it gets.
+    assert_eq!(measurement.gain, Gain::OneEighth);
+    assert_eq!(measurement.integration_time, IntegrationTime::_25ms);
+    assert_eq!(measurement.status, RangeStatus::InRange);
+    assert_eq!(measurement.adjustment, None);
+    #[cfg(feature = "lux_as_f32")]
+    assert!((measurement.lux.0 - 50000.0).abs() < 50.0);
+}
@@ -0,0 +1,82 @@
+use crate::calibration::{fit, Sample};
+use crate::{Calibration, CorrectionModel, Gain, IntegrationTime, Lux};
+
+fn sample(raw: u16, gain: Gain, reference_lux: f32) -> Sample {
+    Sample {
+        raw,
+        gain,
+        integration_time: IntegrationTime::_100ms,
+        reference_lux: Lux(reference_lux),
+    }
+}
+
+#[test]
+fn fit_finds_scale_and_offset() {
+    // 57.6 lx, 115.2 lx and 230.4 lx behind a window with a scale of 2
+    // and an offset of 5 lx.
+    let samples = [
+        sample(1000, Gain::One, 120.2),
+        sample(2000, Gain::One, 235.4),
+        sample(8000, Gain::Two, 465.8),
+    ];
+    let fit = fit(&samples, CorrectionModel::None).unwrap();
+    assert!((fit.calibration.scale - 2.0).abs() < 1e-5);
+    assert!((fit.calibration.offset - 5.0).abs() < 1e-3);
+    assert_eq!(fit.calibration.white_scale, None);
+    assert!(fit.r_squared > 0.999_999);
+    assert!(fit.max_error_lux < 1e-3);
+}
+
+#[test]
+fn fit_reports_the_largest_error() {
+    let samples = [
+        sample(1000, Gain::One, 100.0),
+        sample(2000, Gain::One, 220.0),
+        sample(3000, Gain::One, 300.0),
+    ];
+    let fit = fit(&samples, CorrectionModel::None).unwrap();
+    assert!(fit.r_squared < 1.0);
+    // The best line through (57.6, 100), (115.2, 220) and (172.8, 300)
+    // misses the middle point by 2/3 of 20 lx.
+    assert!((fit.max_error_lux - 40.0 / 3.0).abs() < 1e-3);
+}
+
+#[test]
+fn fit_needs_two_different_sensor_values() {
+    assert_eq!(fit(&[], CorrectionModel::None), None);
+    let samples = [
+        sample(1000, Gain::One, 100.0),
+        sample(1000, Gain::One, 110.0),
+        sample(1000, Gain::One, 120.0),
+    ];
+    assert_eq!(fit(&samples, CorrectionModel::None), None);
+}
+
+#[test]
+fn fit_rejects_decreasing_reference_values() {
+    let samples = [
+        sample(1000, Gain::One, 200.0),
+        sample(2000, Gain::One, 100.0),
+    ];
+    assert_eq!(fit(&samples, CorrectionModel::None), None);
+}
+
+#[test]
+fn bytes_round_trip() {
+    for calibration in [
+        Calibration::default(),
+        Calibration::new(4.0).offset(-0.5),
+        Calibration::new(2.5).offset(1.25).white_scale(3.0),
+    ] {
+        let bytes = calibration.to_bytes();
+        assert_eq!(Calibration::from_bytes(&bytes), Some(calibration));
+    }
+}
+
+#[test]
+fn erased_eeprom_content_is_rejected() {
+    let bytes = [0xFF; Calibration::SERIALIZED_LEN];
+    assert_eq!(Calibration::from_bytes(&bytes), None);
+    let bytes = [0x00; Calibration::SERIALIZED_LEN];
+    assert_eq!(Calibration::from_bytes(&bytes), None);
+}
@@ -0,0 +1,102 @@
+use super::{enabled_device, Bus};
+use crate::blocking::Veml7700;
+use crate::device_impl::Register;
+use crate::sim::{self, SimulatedVeml7700};
+use crate::{ChangeWindow, Error, Threshold, Veml7700Config};
+
+#[test]
+fn rearms_around_the_new_level() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = enabled_device(&sim);
+    device.read_raw_fresh(&mut &sim).unwrap();
+    device
+        .enable_interrupt_on_change(ChangeWindow::Raw(100))
+        .unwrap();
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(1836));
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(1636));
+
+    sim.set_lux(200.0);
+    sim.advance_us(100_000);
+    assert!(sim.is_interrupt_asserted());
+    let status = device.read_interrupt_status().unwrap();
+    assert!(status.was_too_high && !status.was_too_low);
+    assert!(!sim.is_interrupt_asserted());
+    // 200 lx is 3472 counts.
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(3572));
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(3372));
+
+    sim.advance_us(100_000);
+    assert!(!sim.is_interrupt_asserted());
+}
+
+#[test]
+fn status_survives_a_failed_rearm() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    device.enable().unwrap();
+    device.read_raw_fresh(&mut &sim).unwrap();
+    device
+        .enable_interrupt_on_change(ChangeWindow::Raw(100))
+        .unwrap();
+    sim.set_lux(200.0);
+    sim.advance_us(100_000);
+
+    // Reading ALS_INT succeeds, reading ALS for the new window fails.
+    bus.fail_in(1);
+    let status = device.read_interrupt_status().unwrap();
+    assert!(status.was_too_high);
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(1836));
+
+    // The next call re-arms first. If that fails, the error is returned
+    // before the flags are read.
+    sim.set_lux(300.0);
+    sim.advance_us(100_000);
+    assert!(sim.is_interrupt_asserted());
+    bus.fail_in(0);
+    assert!(matches!(
+        device.read_interrupt_status(),
+        Err(Error::I2C(sim::Error::NoAcknowledge))
+    ));
+    assert!(sim.is_interrupt_asserted());
+
+    let status = device.read_interrupt_status().unwrap();
+    assert!(status.was_too_high);
+    assert!(!sim.is_interrupt_asserted());
+    // 300 lx is 5208 counts.
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(5308));
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(5108));
+}
+
+#[test]
+fn apply_ends_a_pending_rearm() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    device.enable().unwrap();
+    device.read_raw_fresh(&mut &sim).unwrap();
+    device
+        .enable_interrupt_on_change(ChangeWindow::Raw(100))
+        .unwrap();
+    sim.set_lux(200.0);
+    sim.advance_us(100_000);
+    bus.fail_in(1);
+    assert!(device.read_interrupt_status().unwrap().was_too_high);
+
+    let config = Veml7700Config::new()
+        .high_threshold(Threshold::Raw(4000))
+        .low_threshold(Threshold::Raw(3000))
+        .interrupts_enabled(true)
+        .enabled(true);
+    device.apply(config).unwrap();
+    bus.take_writes();
+    sim.advance_us(100_000);
+    let status = device.read_interrupt_status().unwrap();
+    assert!(!status.was_too_high && !status.was_too_low);
+    assert!(bus.take_writes().is_empty());
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(4000));
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(3000));
+}
@@ -0,0 +1,52 @@
+use super::Bus;
+use crate::blocking::Veml7700;
+use crate::device_impl::Register;
+use crate::sim::SimulatedVeml7700;
+use crate::{Error, FaultCount, Gain, Threshold, Veml7700Config};
+
+#[test]
+fn apply_only_writes_what_changes() {
+    let sim = SimulatedVeml7700::new();
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    let config = Veml7700Config::new()
+        .gain(Gain::OneQuarter)
+        .fault_count(FaultCount::Four)
+        .high_threshold(Threshold::Raw(5000))
+        .low_threshold(Threshold::Raw(100))
+        .interrupts_enabled(true)
+        .enabled(true);
+    device.apply(config).unwrap();
+    // Thresholds before ALS_CONF, so interrupts only see the new ones.
+    assert_eq!(
+        bus.take_writes(),
+        [Register::ALS_WH, Register::ALS_WL, Register::ALS_CONF]
+    );
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(5000));
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(100));
+
+    device.apply(config.gain(Gain::Two)).unwrap();
+    assert_eq!(bus.take_writes(), [Register::ALS_CONF]);
+
+    device
+        .apply(config.gain(Gain::Two).high_threshold(Threshold::Raw(6000)))
+        .unwrap();
+    assert_eq!(bus.take_writes(), [Register::ALS_WH, Register::ALS_CONF]);
+
+    device
+        .apply(config.gain(Gain::Two).high_threshold(Threshold::Raw(6000)))
+        .unwrap();
+    assert_eq!(bus.take_writes(), [Register::ALS_CONF]);
+}
+
+#[test]
+fn apply_rejects_thresholds_in_the_wrong_order_without_writing() {
+    let sim = SimulatedVeml7700::new();
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    let config = Veml7700Config::new()
+        .high_threshold(Threshold::Raw(100))
+        .low_threshold(Threshold::Raw(5000));
+    assert!(matches!(device.apply(config), Err(Error::InvalidThreshold)));
+    assert!(bus.take_writes().is_empty());
+}
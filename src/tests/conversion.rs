@@ -0,0 +1,48 @@
+use crate::{calculate_raw_threshold_value, convert_raw_als_to_lux, Gain, IntegrationTime};
+
+const ALL_GAINS: [Gain; 4] = [Gain::Two, Gain::One, Gain::OneQuarter, Gain::OneEighth];
+
+const ALL_INTEGRATION_TIMES: [IntegrationTime; 6] = [
+    IntegrationTime::_25ms,
+    IntegrationTime::_50ms,
+    IntegrationTime::_100ms,
+    IntegrationTime::_200ms,
+    IntegrationTime::_400ms,
+    IntegrationTime::_800ms,
+];
+
+#[test]
+fn lux_thresholds_round_trip_to_the_raw_value() {
+    for it in ALL_INTEGRATION_TIMES {
+        for gain in ALL_GAINS {
+            for raw in 0..=u16::MAX {
+                let lux = convert_raw_als_to_lux(it, gain, raw);
+                let round_trip = calculate_raw_threshold_value(it, gain, lux);
+                assert!(raw.abs_diff(round_trip) <= 1, "{it:?} {gain:?} {raw}");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "lux_as_u32")]
+#[test]
+fn millilux_matches_lux_within_2_ppm_or_1_millilux() {
+    use crate::convert_raw_als_to_millilux;
+
+    for it in ALL_INTEGRATION_TIMES {
+        for gain in ALL_GAINS {
+            for raw in 0..=u16::MAX {
+                let lux = f64::from(convert_raw_als_to_lux(it, gain, raw).0) * 1000.0;
+                if lux >= f64::from(u32::MAX) {
+                    continue;
+                }
+                let millilux = f64::from(convert_raw_als_to_millilux(it, gain, raw));
+                let tolerance = (lux * 2e-6).max(1.0);
+                assert!(
+                    (millilux - lux).abs() <= tolerance,
+                    "{it:?} {gain:?} {raw}: {millilux} != {lux}"
+                );
+            }
+        }
+    }
+}
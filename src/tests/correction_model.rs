@@ -0,0 +1,49 @@
+use crate::blocking::Veml7700;
+use crate::calibration::{self, Sample};
+use crate::device_impl::Register;
+use crate::sim::SimulatedVeml7700;
+use crate::{convert_raw_als_to_lux, CorrectionModel, Error, Gain, IntegrationTime, Lux};
+
+/// Rises up to about 6300 lx and then falls, to 0 lx at about 25 klx.
+const NOT_MONOTONIC: CorrectionModel = CorrectionModel::Custom([1.0, 0.0, 0.0, -1e-12]);
+
+#[test]
+fn set_correction_model_rejects_a_model_that_is_not_monotonic() {
+    let sim = SimulatedVeml7700::new();
+    let mut device = Veml7700::new(&sim);
+    device.set_gain(Gain::OneEighth).unwrap();
+    device.set_high_threshold_lux(Lux(8000.0)).unwrap();
+    let threshold = sim.peek_register(Register::ALS_WH);
+    assert!(matches!(
+        device.set_correction_model(NOT_MONOTONIC),
+        Err(Error::InvalidCorrectionModel)
+    ));
+    // The previous model is kept.
+    let expected = convert_raw_als_to_lux(IntegrationTime::_100ms, Gain::OneEighth, 20000);
+    assert_eq!(device.convert_raw_als_to_lux(20000), expected);
+    assert_eq!(sim.peek_register(Register::ALS_WH), threshold);
+}
+
+#[test]
+fn set_correction_model_accepts_an_increasing_model() {
+    let sim = SimulatedVeml7700::new();
+    let mut device = Veml7700::new(&sim);
+    let model = CorrectionModel::Custom([1.1, 1e-6, 0.0, 0.0]);
+    device.set_correction_model(model).unwrap();
+    // 1000 counts at gain 1 and 100 ms are 57.6 lx before correction.
+    let lux = device.convert_raw_als_to_lux(1000).0;
+    assert!((lux - (57.6 * 1.1 + 57.6 * 57.6 * 1e-6)).abs() < 1e-3);
+}
+
+#[test]
+fn fit_rejects_a_model_that_is_not_monotonic() {
+    let sample = |raw, reference_lux| Sample {
+        raw,
+        gain: Gain::One,
+        integration_time: IntegrationTime::_100ms,
+        reference_lux: Lux(reference_lux),
+    };
+    let samples = [sample(1000, 60.0), sample(2000, 120.0)];
+    assert!(calibration::fit(&samples, NOT_MONOTONIC).is_none());
+    assert!(calibration::fit(&samples, CorrectionModel::LowGain).is_some());
+}
@@ -0,0 +1,47 @@
+use super::enabled_device;
+use crate::blocking::Veml7700;
+use crate::sim::SimulatedVeml7700;
+use crate::{ChangeWindow, Error, RangeStatus};
+
+#[test]
+fn reads_work_after_a_warm_reset() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = enabled_device(&sim);
+    device.read_raw_fresh(&mut &sim).unwrap();
+    // A new driver assumes the device is shut down, but reads still return
+    // what the device measured.
+    let mut device = Veml7700::new(&sim);
+    assert_eq!(device.read_raw().unwrap(), 1736);
+    assert_eq!(device.read_white().unwrap(), 1736);
+}
+
+#[test]
+fn interrupt_on_change_needs_an_enabled_device() {
+    let sim = SimulatedVeml7700::new();
+    let mut device = Veml7700::new(&sim);
+    assert!(matches!(
+        device.enable_interrupt_on_change(ChangeWindow::Raw(100)),
+        Err(Error::NotReady)
+    ));
+}
+
+#[test]
+fn strict_read_fails_for_saturated_counts() {
+    let sim = SimulatedVeml7700::new();
+    // More than 65535 counts at gain 1 and 100 ms.
+    sim.set_lux(10000.0);
+    let mut device = enabled_device(&sim);
+    device.read_raw_fresh(&mut &sim).unwrap();
+    let measurement = device.read_measurement().unwrap();
+    assert_eq!(measurement.status, RangeStatus::Saturated);
+    assert!(matches!(
+        device.read_measurement_strict(),
+        Err(Error::Saturated)
+    ));
+
+    sim.set_lux(1000.0);
+    sim.advance_us(100_000);
+    let measurement = device.read_measurement_strict().unwrap();
+    assert_eq!(measurement.status, RangeStatus::InRange);
+}
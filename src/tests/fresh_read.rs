@@ -0,0 +1,60 @@
+use super::enabled_device;
+use crate::sim::SimulatedVeml7700;
+use crate::{Gain, IntegrationTime, PowerSavingMode};
+
+#[test]
+fn waits_for_a_measurement_after_enabling() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = enabled_device(&sim);
+    assert!(device.is_sample_stale());
+    assert_eq!(device.read_raw().unwrap(), 0);
+    let start_us = sim.now_us();
+    // 100 lx at gain 1 and 100 ms is 1736 counts.
+    assert_eq!(device.read_raw_fresh(&mut &sim).unwrap(), 1736);
+    assert!(!device.is_sample_stale());
+    // Start-up time and one integration time, plus 10 % margin.
+    assert_eq!(sim.now_us() - start_us, 114_400);
+    // No further waiting once the sample is fresh.
+    device.read_raw_fresh(&mut &sim).unwrap();
+    assert_eq!(sim.now_us() - start_us, 114_400);
+}
+
+#[test]
+fn waits_for_new_settings() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = enabled_device(&sim);
+    device.read_raw_fresh(&mut &sim).unwrap();
+    sim.advance_us(30_000);
+    device.set_gain(Gain::Two).unwrap();
+    device
+        .set_integration_time(IntegrationTime::_200ms)
+        .unwrap();
+    assert!(device.is_sample_stale());
+    // The measurement in progress still uses gain 1 and 100 ms.
+    sim.advance_us(80_000);
+    assert_eq!(device.read_raw().unwrap(), 1736);
+    assert_eq!(device.read_raw_fresh(&mut &sim).unwrap(), 6944);
+}
+
+#[test]
+fn wait_does_not_add_up_over_repeated_changes() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = enabled_device(&sim);
+    device.enable_power_saving(PowerSavingMode::Four).unwrap();
+    device.read_raw_fresh(&mut &sim).unwrap();
+    for _ in 0..1000 {
+        device
+            .set_integration_time(IntegrationTime::_800ms)
+            .unwrap();
+        device.set_integration_time(IntegrationTime::_25ms).unwrap();
+    }
+    let start_us = sim.now_us();
+    // 100 lx at gain 1 and 25 ms is 434 counts.
+    assert_eq!(device.read_raw_fresh(&mut &sim).unwrap(), 434);
+    // At most a measurement at 800 ms and one at 25 ms, each followed by
+    // the 4 s power-saving wait, plus 10 % margin.
+    assert!(sim.now_us() - start_us <= (4_800_000 + 4_025_000) * 11 / 10);
+}
@@ -0,0 +1,156 @@
+//! Tests of the drivers, mostly against the simulated device.
+
+extern crate std;
+
+use crate::blocking::Veml7700;
+use crate::device_impl::{BitFlags, Register};
+use crate::sim::{self, SimulatedVeml7700};
+use core::cell::{Cell, RefCell};
+use embedded_hal::i2c::{ErrorType, I2c, Operation, SevenBitAddress};
+use std::vec::Vec;
+
+mod asynch;
+mod auto_range;
+#[cfg(feature = "lux_as_f32")]
+mod calibration;
+mod change_window;
+mod config;
+#[cfg(feature = "lux_as_f32")]
+mod conversion;
+#[cfg(feature = "lux_as_f32")]
+mod correction_model;
+mod errors;
+mod fresh_read;
+mod one_shot;
+mod probe;
+mod thresholds;
+mod typestate;
+mod veml3235;
+mod veml6035;
+
+/// Bus to the simulated device recording the written registers and failing
+/// a chosen transaction.
+struct Bus<'a> {
+    sim: &'a SimulatedVeml7700,
+    transactions: Cell<usize>,
+    fail_at: Cell<Option<usize>>,
+    writes: RefCell<Vec<u8>>,
+}
+
+impl<'a> Bus<'a> {
+    fn new(sim: &'a SimulatedVeml7700) -> Self {
+        Bus {
+            sim,
+            transactions: Cell::new(0),
+            fail_at: Cell::new(None),
+            writes: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Fail the `n`th transaction from now, counting from 0.
+    fn fail_in(&self, n: usize) {
+        self.fail_at.set(Some(self.transactions.get() + n));
+    }
+
+    /// Return the registers written since the last call, in order.
+    fn take_writes(&self) -> Vec<u8> {
+        self.writes.take()
+    }
+}
+
+impl ErrorType for &Bus<'_> {
+    type Error = sim::Error;
+}
+
+impl I2c for &Bus<'_> {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let n = self.transactions.get();
+        self.transactions.set(n + 1);
+        if self.fail_at.get() == Some(n) {
+            return Err(sim::Error::NoAcknowledge);
+        }
+        for operation in operations.iter() {
+            if let Operation::Write([register, _, _]) = operation {
+                self.writes.borrow_mut().push(*register);
+            }
+        }
+        let mut sim = self.sim;
+        I2c::transaction(&mut sim, address, operations)
+    }
+}
+
+/// Register map of a device with 16-bit registers answering at `address`,
+/// recording the writes.
+struct Registers {
+    address: SevenBitAddress,
+    values: RefCell<[u16; 256]>,
+    pointer: Cell<u8>,
+    writes: RefCell<Vec<(u8, u16)>>,
+}
+
+impl Registers {
+    fn new(address: SevenBitAddress) -> Self {
+        Registers {
+            address,
+            values: RefCell::new([0; 256]),
+            pointer: Cell::new(0),
+            writes: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn set(&self, register: u8, value: u16) {
+        self.values.borrow_mut()[usize::from(register)] = value;
+    }
+
+    /// Return the register and value of the writes since the last call, in
+    /// order.
+    fn take_writes(&self) -> Vec<(u8, u16)> {
+        self.writes.take()
+    }
+}
+
+impl ErrorType for &Registers {
+    type Error = sim::Error;
+}
+
+impl I2c for &Registers {
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != self.address {
+            return Err(sim::Error::NoAcknowledge);
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write([register]) => self.pointer.set(*register),
+                Operation::Write([register, low, high]) => {
+                    let value = u16::from_le_bytes([*low, *high]);
+                    self.set(*register, value);
+                    self.writes.borrow_mut().push((*register, value));
+                }
+                Operation::Read(buffer) if buffer.len() == 2 => {
+                    let value = self.values.borrow()[usize::from(self.pointer.get())];
+                    buffer.copy_from_slice(&value.to_le_bytes());
+                }
+                _ => return Err(sim::Error::InvalidLength),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn enabled_device(sim: &SimulatedVeml7700) -> Veml7700<&SimulatedVeml7700> {
+    let mut device = Veml7700::new(sim);
+    device.enable().unwrap();
+    device
+}
+
+fn is_shut_down(sim: &SimulatedVeml7700) -> bool {
+    sim.peek_register(Register::ALS_CONF).unwrap() & BitFlags::ALS_SD != 0
+}
@@ -0,0 +1,27 @@
+use super::{is_shut_down, Bus};
+use crate::blocking::Veml7700;
+use crate::sim::SimulatedVeml7700;
+
+#[test]
+fn disables_the_device_after_measuring() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let mut device = Veml7700::new(&sim);
+    let measurement = device.measure_once(&mut &sim).unwrap();
+    assert_eq!(measurement.als.raw, 1736);
+    assert!(is_shut_down(&sim));
+    assert!(!device.settings().enabled);
+}
+
+#[test]
+fn disables_the_device_on_error() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    // Enabling succeeds, reading ALS fails.
+    bus.fail_in(1);
+    assert!(device.measure_once(&mut &sim).is_err());
+    assert!(is_shut_down(&sim));
+    assert!(!device.settings().enabled);
+}
@@ -0,0 +1,40 @@
+use crate::blocking::Veml7700;
+use crate::sim::SimulatedVeml7700;
+use crate::{DeviceId, Error, SlaveAddr};
+
+#[test]
+fn finds_the_device_at_either_address() {
+    let sim = SimulatedVeml7700::new();
+    let mut device = Veml7700::new(&sim);
+    let expected = DeviceId {
+        device_id: 0x81,
+        address_option: 0xC4,
+    };
+    assert_eq!(device.probe().unwrap(), expected);
+
+    let sim = SimulatedVeml7700::new_with_address(SlaveAddr::Alternative);
+    let mut device = Veml7700::new_with_address(&sim, SlaveAddr::Alternative);
+    let expected = DeviceId {
+        device_id: 0x81,
+        address_option: 0xD4,
+    };
+    assert_eq!(device.probe().unwrap(), expected);
+}
+
+#[test]
+fn reports_a_different_id() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_device_id(0x2835);
+    let mut device = Veml7700::new(&sim);
+    assert!(matches!(device.probe(), Err(Error::WrongId(0x2835))));
+    // Reading the ID does not check it.
+    let id = device.read_device_id().unwrap();
+    assert_eq!((id.device_id, id.address_option), (0x35, 0x28));
+}
+
+#[test]
+fn reports_a_missing_device() {
+    let sim = SimulatedVeml7700::new();
+    let mut device = Veml7700::new_with_address(&sim, SlaveAddr::Alternative);
+    assert!(matches!(device.probe(), Err(Error::DeviceNotFound)));
+}
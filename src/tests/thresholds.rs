@@ -0,0 +1,61 @@
+use super::Bus;
+use crate::blocking::Veml7700;
+use crate::device_impl::Register;
+use crate::sim::SimulatedVeml7700;
+use crate::{Gain, Veml7700Config};
+
+#[cfg(feature = "lux_as_f32")]
+#[test]
+fn lux_thresholds_follow_gain_changes_and_settings() {
+    use crate::Lux;
+
+    let sim = SimulatedVeml7700::new();
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    device.set_high_threshold_lux(Lux(100.0)).unwrap();
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(1736));
+    bus.take_writes();
+
+    device.set_gain(Gain::Two).unwrap();
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(3472));
+    assert_eq!(bus.take_writes(), [Register::ALS_WH, Register::ALS_CONF]);
+
+    // Applying a configuration based on the settings keeps the threshold
+    // in lux.
+    let config = Veml7700Config::from(device.settings()).gain(Gain::One);
+    device.apply(config).unwrap();
+    assert_eq!(sim.peek_register(Register::ALS_WH), Some(1736));
+}
+
+#[cfg(feature = "lux_as_u32")]
+#[test]
+fn millilux_thresholds_follow_integration_time_changes() {
+    use crate::IntegrationTime;
+
+    let sim = SimulatedVeml7700::new();
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    // 50 lx at gain 1 and 100 ms is 868 counts.
+    device.set_low_threshold_millilux(50_000).unwrap();
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(868));
+    bus.take_writes();
+
+    device
+        .set_integration_time(IntegrationTime::_400ms)
+        .unwrap();
+    assert_eq!(sim.peek_register(Register::ALS_WL), Some(3472));
+    assert_eq!(bus.take_writes(), [Register::ALS_WL, Register::ALS_CONF]);
+}
+
+#[test]
+fn raw_thresholds_are_not_rewritten() {
+    let sim = SimulatedVeml7700::new();
+    let bus = Bus::new(&sim);
+    let mut device = Veml7700::new(&bus);
+    device.set_thresholds_raw(100, 10_000).unwrap();
+    bus.take_writes();
+    device.set_gain(Gain::Two).unwrap();
+    let config = Veml7700Config::from(device.settings()).gain(Gain::One);
+    device.apply(config).unwrap();
+    assert_eq!(bus.take_writes(), [Register::ALS_CONF, Register::ALS_CONF]);
+}
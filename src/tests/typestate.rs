@@ -0,0 +1,111 @@
+use super::{is_shut_down, Bus};
+use crate::blocking::typestate::Veml7700;
+use crate::sim::{self, SimulatedVeml7700};
+use crate::{ChangeWindow, Error, SlaveAddr};
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType, InputPin};
+
+/// INT pin of the simulated device.
+struct IntPin<'a>(&'a SimulatedVeml7700);
+
+impl ErrorType for IntPin<'_> {
+    type Error = Infallible;
+}
+
+impl InputPin for IntPin<'_> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.0.is_interrupt_asserted())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.0.is_interrupt_asserted())
+    }
+}
+
+#[test]
+fn failed_enable_returns_the_shut_down_device() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let bus = Bus::new(&sim);
+    let device = Veml7700::new(&bus);
+    bus.fail_in(0);
+    let Err((device, error)) = device.enable() else {
+        panic!("enabling should fail");
+    };
+    assert!(matches!(error, Error::I2C(sim::Error::NoAcknowledge)));
+    assert!(!device.settings().enabled);
+    assert!(is_shut_down(&sim));
+
+    let Ok(mut device) = device.enable() else {
+        panic!("enabling should succeed on retry");
+    };
+    assert_eq!(device.read_raw_fresh(&mut &sim).unwrap(), 1736);
+}
+
+#[test]
+fn failed_disable_returns_the_active_device() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let bus = Bus::new(&sim);
+    let Ok(device) = Veml7700::new(&bus).enable() else {
+        panic!("enabling should succeed");
+    };
+    bus.fail_in(0);
+    let Err((mut device, error)) = device.disable() else {
+        panic!("disabling should fail");
+    };
+    assert!(matches!(error, Error::I2C(sim::Error::NoAcknowledge)));
+    assert!(device.settings().enabled);
+    assert_eq!(device.read_raw_fresh(&mut &sim).unwrap(), 1736);
+
+    let Ok(device) = device.disable() else {
+        panic!("disabling should succeed on retry");
+    };
+    assert!(!device.settings().enabled);
+    assert!(is_shut_down(&sim));
+}
+
+#[test]
+fn probe_works_in_both_states() {
+    let sim = SimulatedVeml7700::new();
+    let mut device = Veml7700::new(&sim);
+    device.probe().unwrap();
+    let Ok(mut device) = device.enable() else {
+        panic!("enabling should succeed");
+    };
+    assert_eq!(device.read_device_id().unwrap(), device.probe().unwrap());
+
+    let mut missing = Veml7700::new_with_address(&sim, SlaveAddr::Alternative);
+    assert!(matches!(missing.probe(), Err(Error::DeviceNotFound)));
+}
+
+#[test]
+fn measure_auto_range_leaves_the_device_active() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let Ok(mut device) = Veml7700::new(&sim).enable() else {
+        panic!("enabling should succeed");
+    };
+    device.measure_auto_range(&mut &sim).unwrap();
+    assert!(!is_shut_down(&sim));
+    assert!(device.read_raw().is_ok());
+}
+
+#[test]
+fn wait_for_interrupt_reports_the_change() {
+    let sim = SimulatedVeml7700::new();
+    sim.set_lux(100.0);
+    let Ok(mut device) = Veml7700::new(&sim).enable() else {
+        panic!("enabling should succeed");
+    };
+    device.read_raw_fresh(&mut &sim).unwrap();
+    device
+        .enable_interrupt_on_change(ChangeWindow::Raw(100))
+        .unwrap();
+    sim.set_lux(200.0);
+    let event = device
+        .wait_for_interrupt(&mut IntPin(&sim), &mut &sim, 1_000_000)
+        .unwrap();
+    assert!(event.status.was_too_high);
+    assert_eq!(event.measurement.raw, 3472);
+}
@@ -0,0 +1,97 @@
+use super::Registers;
+use crate::blocking::Veml3235;
+use crate::veml3235::{DigitalGain, Gain, IntegrationTime};
+
+/// The same address as the VEML7700.
+const ADDRESS: u8 = 0x10;
+const CONF: u8 = 0x00;
+const WHITE: u8 = 0x04;
+const ALS: u8 = 0x05;
+
+#[test]
+fn configuration_bits() {
+    let registers = Registers::new(ADDRESS);
+    let mut device = Veml3235::new(&registers);
+    device.enable().unwrap();
+    // SD at bit 15 and SD0 at bit 0 are both cleared.
+    assert_eq!(registers.take_writes(), [(CONF, 0x0000)]);
+
+    device
+        .set_integration_time(IntegrationTime::_800ms)
+        .unwrap();
+    device.set_gain(Gain::Four).unwrap();
+    device.set_digital_gain(DigitalGain::Two).unwrap();
+    // Integration time at bits 6:4, gain at bits 12:11, DG at bit 13.
+    assert_eq!(
+        registers.take_writes(),
+        [
+            (CONF, 0b100 << 4),
+            (CONF, 0b11 << 11 | 0b100 << 4),
+            (CONF, 1 << 13 | 0b11 << 11 | 0b100 << 4),
+        ]
+    );
+
+    device.set_gain(Gain::Two).unwrap();
+    device
+        .set_integration_time(IntegrationTime::_100ms)
+        .unwrap();
+    device.set_digital_gain(DigitalGain::One).unwrap();
+    device.disable().unwrap();
+    assert_eq!(
+        registers.take_writes(),
+        [
+            (CONF, 1 << 13 | 0b01 << 11 | 0b100 << 4),
+            (CONF, 1 << 13 | 0b01 << 11 | 0b001 << 4),
+            (CONF, 0b01 << 11 | 0b001 << 4),
+            (CONF, 1 << 15 | 0b01 << 11 | 0b001 << 4 | 1),
+        ]
+    );
+}
+
+#[test]
+fn reads_the_als_and_white_registers() {
+    let registers = Registers::new(ADDRESS);
+    registers.set(ALS, 1234);
+    registers.set(WHITE, 5678);
+    let mut device = Veml3235::new(&registers);
+    assert_eq!(device.read_raw().unwrap(), 1234);
+    assert_eq!(device.read_white().unwrap(), 5678);
+}
+
+#[cfg(feature = "lux_as_f32")]
+#[test]
+fn lux_conversion() {
+    use crate::veml3235::convert_raw_als_to_lux;
+
+    // 0.00213 lx/count at the highest sensitivity.
+    let lux = convert_raw_als_to_lux(IntegrationTime::_800ms, Gain::Four, DigitalGain::Two, 1000);
+    assert!((lux.0 - 2.13).abs() < 1e-5);
+    // 128 times that at the lowest sensitivity.
+    let lux = convert_raw_als_to_lux(IntegrationTime::_50ms, Gain::One, DigitalGain::One, 1000);
+    assert!((lux.0 - 272.64).abs() < 1e-3);
+    let lux = convert_raw_als_to_lux(IntegrationTime::_200ms, Gain::Two, DigitalGain::One, 1000);
+    assert!((lux.0 - 34.08).abs() < 1e-4);
+
+    let registers = Registers::new(ADDRESS);
+    registers.set(ALS, 1000);
+    let mut device = Veml3235::new(&registers);
+    device.set_gain(Gain::Four).unwrap();
+    device
+        .set_integration_time(IntegrationTime::_800ms)
+        .unwrap();
+    device.set_digital_gain(DigitalGain::Two).unwrap();
+    assert!((device.read_lux().unwrap().0 - 2.13).abs() < 1e-5);
+}
+
+#[cfg(feature = "lux_as_u32")]
+#[test]
+fn millilux_conversion() {
+    use crate::veml3235::convert_raw_als_to_millilux;
+
+    let millilux =
+        convert_raw_als_to_millilux(IntegrationTime::_800ms, Gain::Four, DigitalGain::Two, 1000);
+    assert_eq!(millilux, 2130);
+    let millilux =
+        convert_raw_als_to_millilux(IntegrationTime::_50ms, Gain::One, DigitalGain::One, 1000);
+    assert_eq!(millilux, 272_640);
+}
@@ -0,0 +1,123 @@
+use super::Registers;
+use crate::blocking::Veml6035;
+use crate::device_impl::{BitFlags, Register};
+use crate::veml6035::{DigitalGain, Gain, Sensitivity};
+use crate::IntegrationTime;
+
+const ADDRESS: u8 = 0x29;
+
+#[test]
+fn configuration_bits() {
+    let registers = Registers::new(ADDRESS);
+    let mut device = Veml6035::new(&registers);
+    device.enable().unwrap();
+    assert_eq!(registers.take_writes(), [(Register::ALS_CONF, 0)]);
+
+    device.enable_white_channel().unwrap();
+    device.set_gain(Gain::Two).unwrap();
+    device.set_digital_gain(DigitalGain::Two).unwrap();
+    device.set_sensitivity(Sensitivity::Low).unwrap();
+    // The integration time is encoded like for the VEML7700.
+    device
+        .set_integration_time(IntegrationTime::_800ms)
+        .unwrap();
+    device.disable().unwrap();
+    assert_eq!(
+        registers.take_writes(),
+        [
+            (Register::ALS_CONF, 1 << 2),
+            (Register::ALS_CONF, 1 << 10 | 1 << 2),
+            (Register::ALS_CONF, 1 << 11 | 1 << 10 | 1 << 2),
+            (Register::ALS_CONF, 1 << 12 | 1 << 11 | 1 << 10 | 1 << 2),
+            (
+                Register::ALS_CONF,
+                1 << 12 | 1 << 11 | 1 << 10 | 0b0011 << 6 | 1 << 2
+            ),
+            (
+                Register::ALS_CONF,
+                1 << 12 | 1 << 11 | 1 << 10 | 0b0011 << 6 | 1 << 2 | 1
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reads_the_measurement_and_interrupt_registers() {
+    let registers = Registers::new(ADDRESS);
+    registers.set(Register::ALS, 1234);
+    registers.set(Register::WHITE, 5678);
+    registers.set(Register::ALS_INT, BitFlags::INT_TH_HIGH);
+    let mut device = Veml6035::new(&registers);
+    assert_eq!(device.read_raw().unwrap(), 1234);
+    assert_eq!(device.read_white().unwrap(), 5678);
+    let status = device.read_interrupt_status().unwrap();
+    assert!(status.was_too_high && !status.was_too_low);
+}
+
+#[cfg(feature = "lux_as_f32")]
+#[test]
+fn lux_conversion_and_thresholds() {
+    use crate::veml6035::convert_raw_als_to_lux;
+    use crate::Lux;
+
+    // 0.0004 lx/count at the highest sensitivity.
+    let lux = convert_raw_als_to_lux(
+        IntegrationTime::_800ms,
+        Gain::Two,
+        DigitalGain::Two,
+        Sensitivity::High,
+        2500,
+    );
+    assert!((lux.0 - 1.0).abs() < 1e-5);
+    // 0.1024 lx/count at 100 ms and the lowest sensitivity.
+    let lux = convert_raw_als_to_lux(
+        IntegrationTime::_100ms,
+        Gain::One,
+        DigitalGain::One,
+        Sensitivity::Low,
+        1000,
+    );
+    assert!((lux.0 - 102.4).abs() < 1e-3);
+
+    let registers = Registers::new(ADDRESS);
+    let mut device = Veml6035::new(&registers);
+    device.set_sensitivity(Sensitivity::Low).unwrap();
+    registers.take_writes();
+    device.set_thresholds_lux(Lux(10.24), Lux(102.4)).unwrap();
+    assert_eq!(
+        registers.take_writes(),
+        [(Register::ALS_WL, 100), (Register::ALS_WH, 1000)]
+    );
+    // 6710.8 lx is the most 65535 counts can represent.
+    assert!(device.set_high_threshold_lux(Lux(6720.0)).is_err());
+    assert!(registers.take_writes().is_empty());
+}
+
+#[cfg(feature = "lux_as_u32")]
+#[test]
+fn millilux_conversion_and_thresholds() {
+    use crate::veml6035::{calculate_raw_threshold_value_millilux, convert_raw_als_to_millilux};
+
+    let millilux = convert_raw_als_to_millilux(
+        IntegrationTime::_800ms,
+        Gain::Two,
+        DigitalGain::Two,
+        Sensitivity::High,
+        2500,
+    );
+    assert_eq!(millilux, 1000);
+    const RAW: u16 = calculate_raw_threshold_value_millilux(
+        IntegrationTime::_100ms,
+        Gain::One,
+        DigitalGain::One,
+        Sensitivity::Low,
+        102_400,
+    );
+    assert_eq!(RAW, 1000);
+
+    let registers = Registers::new(ADDRESS);
+    let mut device = Veml6035::new(&registers);
+    device.set_high_threshold_millilux(1_000).unwrap();
+    // 12.8 mlx/count at 100 ms, gain 1, digital gain 1 and high sensitivity.
+    assert_eq!(registers.take_writes(), [(Register::ALS_WH, 78)]);
+}
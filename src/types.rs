@@ -79,6 +79,18 @@ pub enum PowerSavingMode {
     Four,
 }
 
+impl PowerSavingMode {
+    /// Return the wait time between measurements in milliseconds
+    pub fn wait_time_ms(&self) -> u16 {
+        match self {
+            PowerSavingMode::One => 500,
+            PowerSavingMode::Two => 1000,
+            PowerSavingMode::Three => 2000,
+            PowerSavingMode::Four => 4000,
+        }
+    }
+}
+
 /// Interrupt status
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct InterruptStatus {
@@ -89,3 +101,430 @@ pub struct InterruptStatus {
     /// as configured as fault count.
     pub was_too_high: bool,
 }
+
+/// Interrupt status together with the measurement read right after it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterruptEvent {
+    /// Interrupt status, cleared on the device when read.
+    pub status: InterruptStatus,
+    /// ALS measurement.
+    pub measurement: Measurement,
+}
+
+/// Illuminance in lux
+///
+/// ```
+/// use veml7700::Lux;
+///
+/// let lux = Lux::from_foot_candles(10.0);
+/// assert!(lux > Lux(107.0) && lux < Lux(108.0));
+/// assert_eq!(f32::from(Lux(250.0)), 250.0);
+/// ```
+#[cfg(feature = "lux_as_f32")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Lux(pub f32);
+
+#[cfg(feature = "lux_as_f32")]
+impl Lux {
+    /// Lux in one foot-candle (one lumen per square foot).
+    pub const PER_FOOT_CANDLE: f32 = 10.763_911;
+
+    /// Illuminance in lux.
+    pub const fn lux(self) -> f32 {
+        self.0
+    }
+
+    /// Convert an illuminance in foot-candles.
+    pub fn from_foot_candles(foot_candles: f32) -> Self {
+        Lux(foot_candles * Self::PER_FOOT_CANDLE)
+    }
+
+    /// Illuminance in foot-candles.
+    pub fn foot_candles(self) -> f32 {
+        self.0 / Self::PER_FOOT_CANDLE
+    }
+}
+
+#[cfg(feature = "lux_as_f32")]
+impl From<f32> for Lux {
+    fn from(lux: f32) -> Self {
+        Lux(lux)
+    }
+}
+
+#[cfg(feature = "lux_as_f32")]
+impl From<Lux> for f32 {
+    fn from(lux: Lux) -> Self {
+        lux.0
+    }
+}
+
+/// Formats the value like `f32`, honoring the precision, followed by ` lx`.
+#[cfg(feature = "lux_as_f32")]
+impl core::fmt::Display for Lux {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)?;
+        f.write_str(" lx")
+    }
+}
+
+/// Illuminance quantity of `uom` in `f32`, converted to and from `Lux`
+///
+/// `uom` has no quantity named illuminance. One lux is one lumen or candela
+/// steradian per square meter, and as the steradian is dimensionless this has
+/// the dimension L⁻²J of `uom::si::f32::Luminance`, which is therefore the
+/// same type. Its base unit, candela per square meter, is numerically one lux.
+///
+/// ```
+/// use veml7700::{Illuminance, Lux};
+///
+/// let illuminance: Illuminance = Lux(250.0).into();
+/// assert_eq!(illuminance.value, 250.0);
+/// assert_eq!(Lux::from(illuminance), Lux(250.0));
+/// ```
+#[cfg(feature = "uom")]
+pub type Illuminance = uom::si::f32::Luminance;
+
+#[cfg(feature = "uom")]
+impl From<Lux> for Illuminance {
+    fn from(lux: Lux) -> Self {
+        use uom::si::luminance::candela_per_square_meter;
+        Illuminance::new::<candela_per_square_meter>(lux.0)
+    }
+}
+
+#[cfg(feature = "uom")]
+impl From<Illuminance> for Lux {
+    fn from(illuminance: Illuminance) -> Self {
+        use uom::si::luminance::candela_per_square_meter;
+        Lux(illuminance.get::<candela_per_square_meter>())
+    }
+}
+
+/// ALS measurement together with the settings it was taken with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    /// Raw ALS counts.
+    pub raw: u16,
+    /// Gain used for the measurement.
+    pub gain: Gain,
+    /// Integration time used for the measurement.
+    pub integration_time: IntegrationTime,
+    /// Whether the raw counts are in the useful range of the settings.
+    pub status: RangeStatus,
+    /// Recommended adjustment of the gain or integration time, or `None` if
+    /// the measurement is in range or the settings cannot be adjusted any
+    /// further in the necessary direction.
+    pub adjustment: Option<RangeAdjustment>,
+    /// Compensated lux value.
+    #[cfg(feature = "lux_as_f32")]
+    pub lux: Lux,
+    /// Compensated value in millilux.
+    #[cfg(feature = "lux_as_u32")]
+    pub millilux: u32,
+}
+
+/// Range of an ALS measurement relative to the settings it was taken with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeStatus {
+    /// The counts are in the useful range.
+    InRange,
+    /// The counts are 100 or less, so the resolution of the measurement
+    /// is poor.
+    UnderRange,
+    /// The counts are 65000 or more, close to the end of the 16-bit range,
+    /// so the real illuminance may be higher than measured.
+    Saturated,
+}
+
+/// Direction in which to adjust the gain and integration time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeAdjustment {
+    /// Increase the gain or the integration time.
+    IncreaseSensitivity,
+    /// Decrease the integration time or the gain.
+    DecreaseSensitivity,
+}
+
+/// Contents of the ID register
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeviceId {
+    /// Device ID code: 0x81 for the VEML7700 and the VEML6030.
+    pub device_id: u8,
+    /// Slave address option code: 0xC4 for the address 0x10 and 0xD4 for
+    /// the address 0x48.
+    pub address_option: u8,
+}
+
+/// Possible slave addresses
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SlaveAddr {
+    /// Default slave address: 0x10
+    #[default]
+    Default,
+    /// Alternative slave address: 0x48
+    ///
+    /// Only the VEML6030 supports this, selected with its ADDR pin high.
+    Alternative,
+}
+
+/// Measurement of both channels taken with `measure_once()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OneShotMeasurement {
+    /// ALS measurement.
+    pub als: Measurement,
+    /// White channel measurement in raw format.
+    pub white: u16,
+}
+
+/// Snapshot of the device configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Gain.
+    pub gain: Gain,
+    /// Integration time.
+    pub integration_time: IntegrationTime,
+    /// Number of consecutive threshold crossings that trigger an interrupt.
+    pub fault_count: FaultCount,
+    /// Whether interrupt generation is enabled.
+    pub interrupts_enabled: bool,
+    /// Whether the device is enabled (not shut down).
+    pub enabled: bool,
+    /// Power-saving mode, if enabled.
+    pub power_saving: Option<PowerSavingMode>,
+    /// ALS high threshold in raw format.
+    pub high_threshold: u16,
+    /// ALS low threshold in raw format.
+    pub low_threshold: u16,
+    /// ALS high threshold as it was set, for example in lux. Thresholds in
+    /// lux or millilux are converted again whenever the gain or the
+    /// integration time change.
+    pub high_threshold_setting: Threshold,
+    /// ALS low threshold as it was set. See `high_threshold_setting`.
+    pub low_threshold_setting: Threshold,
+}
+
+/// ALS threshold for `Veml7700Config`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Threshold {
+    /// Threshold in raw format.
+    Raw(u16),
+    /// Threshold in lux, converted with the gain and integration time of
+    /// the configuration it is part of.
+    #[cfg(feature = "lux_as_f32")]
+    Lux(Lux),
+    /// Threshold in millilux, converted with the gain and integration time of
+    /// the configuration it is part of.
+    #[cfg(feature = "lux_as_u32")]
+    Millilux(u32),
+}
+
+/// Non-linearity correction applied to lux values
+///
+/// The same model is used for converting measurements to lux and for
+/// calculating raw thresholds from lux.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum CorrectionModel {
+    /// No correction.
+    None,
+    /// Compensation formula of the VEML7700 application note for values
+    /// higher than 1000 lx at 1/4 and 1/8 gain (default).
+    #[default]
+    LowGain,
+    /// Compensation formula of the VEML7700 application note for all values
+    /// and settings.
+    AllSettings,
+    /// Polynomial with the given coefficients of `lux`, `lux^2`, `lux^3` and
+    /// `lux^4` for all values and settings.
+    ///
+    /// The polynomial must be strictly increasing over the range of the
+    /// sensor, up to about 120.8 klx before correction, so that thresholds
+    /// can be calculated.
+    #[cfg(feature = "lux_as_f32")]
+    Custom([f32; 4]),
+}
+
+/// Calibration for a window or cover glass in front of the sensor
+///
+/// Lux values are calculated as `lux * scale + offset`, where `scale` is the
+/// inverse of the transmittance of the window. Thresholds in lux are
+/// converted back, so they refer to the illuminance outside of the window.
+/// The white channel uses its own factor if set and `scale` otherwise.
+///
+/// ```
+/// use veml7700::Calibration;
+///
+/// // Window letting 25 % of the light through.
+/// let calibration = Calibration::new(4.0).offset(0.5);
+/// ```
+#[cfg(feature = "lux_as_f32")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub(crate) scale: f32,
+    pub(crate) offset: f32,
+    pub(crate) white_scale: Option<f32>,
+}
+
+#[cfg(feature = "lux_as_f32")]
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(feature = "lux_as_f32")]
+impl Calibration {
+    /// Create a calibration with the given scale factor, without offset.
+    pub const fn new(scale: f32) -> Self {
+        Calibration {
+            scale,
+            offset: 0.0,
+            white_scale: None,
+        }
+    }
+
+    /// Set the offset in lux added after scaling.
+    pub const fn offset(mut self, lux: f32) -> Self {
+        self.offset = lux;
+        self
+    }
+
+    /// Set a separate scale factor for the white channel.
+    pub const fn white_scale(mut self, scale: f32) -> Self {
+        self.white_scale = Some(scale);
+        self
+    }
+}
+
+/// Window around the current measurement for `enable_interrupt_on_change()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ChangeWindow {
+    /// Change relative to the current measurement in percent.
+    Percent(u8),
+    /// Change in raw counts.
+    Raw(u16),
+    /// Change in lux.
+    #[cfg(feature = "lux_as_f32")]
+    Lux(Lux),
+    /// Change in millilux.
+    #[cfg(feature = "lux_as_u32")]
+    Millilux(u32),
+}
+
+/// Complete device configuration, written with `apply()`
+///
+/// The default is the power-on configuration of the device. Start from
+/// `settings()` to only change some of the current settings. Thresholds set
+/// in lux or millilux are carried over as such, so they are converted for
+/// the new gain and integration time:
+///
+/// ```
+/// use veml7700::{Gain, IntegrationTime, Threshold, Veml7700Config};
+///
+/// let config = Veml7700Config::new()
+///     .gain(Gain::OneQuarter)
+///     .integration_time(IntegrationTime::_50ms)
+///     .high_threshold(Threshold::Raw(5000))
+///     .interrupts_enabled(true)
+///     .enabled(true);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Veml7700Config {
+    pub(crate) gain: Gain,
+    pub(crate) integration_time: IntegrationTime,
+    pub(crate) fault_count: FaultCount,
+    pub(crate) interrupts_enabled: bool,
+    pub(crate) enabled: bool,
+    pub(crate) power_saving: Option<PowerSavingMode>,
+    pub(crate) high_threshold: Threshold,
+    pub(crate) low_threshold: Threshold,
+}
+
+impl Default for Veml7700Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Veml7700Config {
+    /// Create a configuration with the power-on settings of the device.
+    pub const fn new() -> Self {
+        Veml7700Config {
+            gain: Gain::One,
+            integration_time: IntegrationTime::_100ms,
+            fault_count: FaultCount::One,
+            interrupts_enabled: false,
+            enabled: false,
+            power_saving: None,
+            high_threshold: Threshold::Raw(0),
+            low_threshold: Threshold::Raw(0),
+        }
+    }
+
+    /// Set the gain.
+    pub const fn gain(mut self, gain: Gain) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Set the integration time.
+    pub const fn integration_time(mut self, it: IntegrationTime) -> Self {
+        self.integration_time = it;
+        self
+    }
+
+    /// Set the number of consecutive threshold crossings that trigger an
+    /// interrupt.
+    pub const fn fault_count(mut self, fc: FaultCount) -> Self {
+        self.fault_count = fc;
+        self
+    }
+
+    /// Set whether interrupt generation is enabled.
+    pub const fn interrupts_enabled(mut self, enabled: bool) -> Self {
+        self.interrupts_enabled = enabled;
+        self
+    }
+
+    /// Set whether the device is enabled (not shut down).
+    pub const fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Set the power-saving mode, or `None` to disable it.
+    pub const fn power_saving(mut self, psm: Option<PowerSavingMode>) -> Self {
+        self.power_saving = psm;
+        self
+    }
+
+    /// Set the ALS high threshold.
+    pub const fn high_threshold(mut self, threshold: Threshold) -> Self {
+        self.high_threshold = threshold;
+        self
+    }
+
+    /// Set the ALS low threshold.
+    pub const fn low_threshold(mut self, threshold: Threshold) -> Self {
+        self.low_threshold = threshold;
+        self
+    }
+}
+
+impl From<Settings> for Veml7700Config {
+    fn from(settings: Settings) -> Self {
+        Veml7700Config {
+            gain: settings.gain,
+            integration_time: settings.integration_time,
+            fault_count: settings.fault_count,
+            interrupts_enabled: settings.interrupts_enabled,
+            enabled: settings.enabled,
+            power_saving: settings.power_saving,
+            high_threshold: settings.high_threshold_setting,
+            low_threshold: settings.low_threshold_setting,
+        }
+    }
+}
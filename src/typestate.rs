@@ -0,0 +1,446 @@
+//! Driver tracking whether the device is enabled in its type.
+//!
+//! [`Veml7700<I2C, Shutdown>`](Veml7700) only allows configuring the device.
+//! [`enable()`](Veml7700::enable) consumes it and returns a
+//! [`Veml7700<I2C, Active>`](Veml7700), which additionally allows reading
+//! measurements, so reading a device that is still shut down does not compile:
+//!
+//! ```compile_fail
+//! use embedded_hal::i2c::I2c;
+//! use veml7700::blocking::typestate::{Shutdown, Veml7700};
+//!
+//! fn read<I2C: I2c>(sensor: &mut Veml7700<I2C, Shutdown>) {
+//!     let _ = sensor.read_raw();
+//! }
+//! ```
+//!
+//! The wrapped driver is the one of the parent module. Methods changing the
+//! device state on their own are only available in the matching state:
+//! `measure_once()` for a shut-down device and `measure_auto_range()` and
+//! `wait_for_interrupt()` for an active one. `sync_from_device()` and the
+//! other methods taking over the configuration of the device are not
+//! available, as the device state would not be known at compile time.
+
+use super::{maybe_async, DelayNs, I2c, SevenBitAddress};
+#[cfg(feature = "lux_as_f32")]
+use crate::{Calibration, Lux};
+use crate::{
+    ChangeWindow, CorrectionModel, DeviceId, Error, FaultCount, Gain, IntegrationTime,
+    InterruptStatus, Measurement, OneShotMeasurement, PowerSavingMode, Settings, SlaveAddr,
+};
+use core::marker::PhantomData;
+
+/// The device is shut down.
+#[derive(Debug)]
+pub struct Shutdown;
+
+/// The device is enabled and measuring.
+#[derive(Debug)]
+pub struct Active;
+
+/// Result of a state change: the device in the new state, or on error the
+/// unchanged device together with the error.
+pub type Transition<I2C, FROM, TO> = Result<
+    Veml7700<I2C, TO>,
+    (
+        Veml7700<I2C, FROM>,
+        Error<<I2C as embedded_hal::i2c::ErrorType>::Error>,
+    ),
+>;
+
+/// VEML7700 device driver with the device state in its type.
+#[derive(Debug)]
+pub struct Veml7700<I2C, STATE> {
+    /// Also used by the parent module for the methods that differ between
+    /// the blocking and the async driver.
+    pub(super) driver: super::Veml7700<I2C>,
+    _state: PhantomData<STATE>,
+}
+
+impl<I2C> Veml7700<I2C, Shutdown>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Create new instance of the VEML7700 device.
+    ///
+    /// This assumes the device is in its power-on state (shut down).
+    pub fn new(i2c: I2C) -> Self {
+        Self::new_with_address(i2c, SlaveAddr::default())
+    }
+
+    /// Create new instance of the device with the given slave address.
+    ///
+    /// Only the VEML6030 supports the alternative address.
+    pub fn new_with_address(i2c: I2C, address: SlaveAddr) -> Self {
+        Veml7700 {
+            driver: super::Veml7700::new_with_address(i2c, address),
+            _state: PhantomData,
+        }
+    }
+
+    /// Enable the device.
+    ///
+    /// See `read_raw_fresh()` for waiting for the first measurement.
+    ///
+    /// On error the device is handed back unchanged together with the error,
+    /// so that enabling can be retried.
+    #[maybe_async]
+    pub async fn enable(mut self) -> Transition<I2C, Shutdown, Active> {
+        match self.driver.enable().await {
+            Ok(()) => Ok(Veml7700 {
+                driver: self.driver,
+                _state: PhantomData,
+            }),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Take a single measurement of both channels and shut the device down
+    /// again.
+    ///
+    /// See the `measure_once()` method of the wrapped driver.
+    #[maybe_async]
+    pub async fn measure_once<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<OneShotMeasurement, Error<I2C::Error>> {
+        self.driver.measure_once(delay).await
+    }
+}
+
+impl<I2C> Veml7700<I2C, Active>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Disable the device (shutdown).
+    ///
+    /// On error the device is handed back unchanged together with the error,
+    /// so that disabling can be retried.
+    #[maybe_async]
+    pub async fn disable(mut self) -> Transition<I2C, Active, Shutdown> {
+        match self.driver.disable().await {
+            Ok(()) => Ok(Veml7700 {
+                driver: self.driver,
+                _state: PhantomData,
+            }),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Read ALS high resolution output data in raw format
+    ///
+    /// Right after enabling the device or changing the settings, see
+    /// `read_raw_fresh()`.
+    #[maybe_async]
+    pub async fn read_raw(&mut self) -> Result<u16, Error<I2C::Error>> {
+        self.driver.read_raw().await
+    }
+
+    /// Read ALS high resolution output data in raw format, waiting for
+    /// a measurement taken with the current settings if necessary.
+    #[maybe_async]
+    pub async fn read_raw_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<u16, Error<I2C::Error>> {
+        self.driver.read_raw_fresh(delay).await
+    }
+
+    /// Read ALS high resolution output data converted to lux
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_lux(&mut self) -> Result<Lux, Error<I2C::Error>> {
+        self.driver.read_lux().await
+    }
+
+    /// Read ALS high resolution output data converted to lux, waiting for
+    /// a measurement taken with the current settings if necessary.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_lux_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Lux, Error<I2C::Error>> {
+        self.driver.read_lux_fresh(delay).await
+    }
+
+    /// Read ALS high resolution output data converted to millilux
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn read_millilux(&mut self) -> Result<u32, Error<I2C::Error>> {
+        self.driver.read_millilux().await
+    }
+
+    /// Read ALS high resolution output data converted to millilux, waiting
+    /// for a measurement taken with the current settings if necessary.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn read_millilux_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<u32, Error<I2C::Error>> {
+        self.driver.read_millilux_fresh(delay).await
+    }
+
+    /// Read an ALS measurement together with the settings it was taken with.
+    #[maybe_async]
+    pub async fn read_measurement(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        self.driver.read_measurement().await
+    }
+
+    /// Read an ALS measurement, failing with `Error::Saturated` for saturated
+    /// counts.
+    #[maybe_async]
+    pub async fn read_measurement_strict(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        self.driver.read_measurement_strict().await
+    }
+
+    /// Read an ALS measurement together with the settings it was taken with,
+    /// waiting for a measurement taken with the current settings if necessary.
+    #[maybe_async]
+    pub async fn read_measurement_fresh<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Measurement, Error<I2C::Error>> {
+        self.driver.read_measurement_fresh(delay).await
+    }
+
+    /// Read white channel measurement
+    #[maybe_async]
+    pub async fn read_white(&mut self) -> Result<u16, Error<I2C::Error>> {
+        self.driver.read_white().await
+    }
+
+    /// Generate an interrupt whenever the light level leaves a window around
+    /// the current measurement.
+    ///
+    /// See the `enable_interrupt_on_change()` method of the wrapped driver.
+    #[maybe_async]
+    pub async fn enable_interrupt_on_change(
+        &mut self,
+        window: ChangeWindow,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.enable_interrupt_on_change(window).await
+    }
+
+    /// Read white channel measurement scaled with the white factor of the
+    /// calibration.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_white_calibrated(&mut self) -> Result<f32, Error<I2C::Error>> {
+        self.driver.read_white_calibrated().await
+    }
+
+    /// Read whether an interrupt has occurred.
+    #[maybe_async]
+    pub async fn read_interrupt_status(&mut self) -> Result<InterruptStatus, Error<I2C::Error>> {
+        self.driver.read_interrupt_status().await
+    }
+
+    /// Return whether the ALS register may still hold a value measured with
+    /// settings older than the current ones.
+    pub fn is_sample_stale(&self) -> bool {
+        self.driver.is_sample_stale()
+    }
+
+    /// Measure with automatic gain and integration time selection.
+    ///
+    /// See the `measure_auto_range()` method of the wrapped driver. The
+    /// device is left enabled.
+    #[maybe_async]
+    pub async fn measure_auto_range<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Measurement, Error<I2C::Error>> {
+        self.driver.measure_auto_range(delay).await
+    }
+}
+
+impl<I2C, STATE> Veml7700<I2C, STATE>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.driver.destroy()
+    }
+
+    /// Get the current configuration as known to the driver.
+    pub fn settings(&self) -> Settings {
+        self.driver.settings()
+    }
+
+    /// Read the ID register.
+    #[maybe_async]
+    pub async fn read_device_id(&mut self) -> Result<DeviceId, Error<I2C::Error>> {
+        self.driver.read_device_id().await
+    }
+
+    /// Check that the device is present and is a VEML7700 or VEML6030.
+    ///
+    /// See the `probe()` method of the wrapped driver.
+    #[maybe_async]
+    pub async fn probe(&mut self) -> Result<DeviceId, Error<I2C::Error>> {
+        self.driver.probe().await
+    }
+
+    /// Set the integration time.
+    #[maybe_async]
+    pub async fn set_integration_time(
+        &mut self,
+        it: IntegrationTime,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_integration_time(it).await
+    }
+
+    /// Set the gain.
+    #[maybe_async]
+    pub async fn set_gain(&mut self, gain: Gain) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_gain(gain).await
+    }
+
+    /// Set the non-linearity correction applied to lux values.
+    #[maybe_async]
+    pub async fn set_correction_model(
+        &mut self,
+        model: CorrectionModel,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_correction_model(model).await
+    }
+
+    /// Set the calibration for a window or cover glass in front of the
+    /// sensor.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_calibration(
+        &mut self,
+        calibration: Calibration,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_calibration(calibration).await
+    }
+
+    /// Set the number of times a threshold crossing must happen consecutively
+    /// to trigger an interrupt.
+    #[maybe_async]
+    pub async fn set_fault_count(&mut self, fc: FaultCount) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_fault_count(fc).await
+    }
+
+    /// Enable interrupt generation.
+    #[maybe_async]
+    pub async fn enable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.driver.enable_interrupts().await
+    }
+
+    /// Disable interrupt generation.
+    #[maybe_async]
+    pub async fn disable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.driver.disable_interrupts().await
+    }
+
+    /// Stop re-programming the thresholds after interrupts and disable
+    /// interrupt generation.
+    #[maybe_async]
+    pub async fn disable_interrupt_on_change(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.driver.disable_interrupt_on_change().await
+    }
+
+    /// Set the ALS high threshold in raw format
+    #[maybe_async]
+    pub async fn set_high_threshold_raw(
+        &mut self,
+        threshold: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_high_threshold_raw(threshold).await
+    }
+
+    /// Set the ALS low threshold in raw format
+    #[maybe_async]
+    pub async fn set_low_threshold_raw(&mut self, threshold: u16) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_low_threshold_raw(threshold).await
+    }
+
+    /// Set the ALS low and high thresholds in raw format.
+    #[maybe_async]
+    pub async fn set_thresholds_raw(
+        &mut self,
+        low: u16,
+        high: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_thresholds_raw(low, high).await
+    }
+
+    /// Set the ALS high threshold in lux.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_high_threshold_lux(&mut self, lux: Lux) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_high_threshold_lux(lux).await
+    }
+
+    /// Set the ALS low threshold in lux.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_low_threshold_lux(&mut self, lux: Lux) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_low_threshold_lux(lux).await
+    }
+
+    /// Set the ALS low and high thresholds in lux.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_thresholds_lux(
+        &mut self,
+        low: Lux,
+        high: Lux,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_thresholds_lux(low, high).await
+    }
+
+    /// Set the ALS high threshold in millilux.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_high_threshold_millilux(
+        &mut self,
+        millilux: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_high_threshold_millilux(millilux).await
+    }
+
+    /// Set the ALS low threshold in millilux.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_low_threshold_millilux(
+        &mut self,
+        millilux: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_low_threshold_millilux(millilux).await
+    }
+
+    /// Set the ALS low and high thresholds in millilux.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_thresholds_millilux(
+        &mut self,
+        low: u32,
+        high: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.set_thresholds_millilux(low, high).await
+    }
+
+    /// Enable the power-saving mode
+    #[maybe_async]
+    pub async fn enable_power_saving(
+        &mut self,
+        psm: PowerSavingMode,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.driver.enable_power_saving(psm).await
+    }
+
+    /// Disable the power-saving mode
+    #[maybe_async]
+    pub async fn disable_power_saving(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.driver.disable_power_saving().await
+    }
+}
@@ -0,0 +1,160 @@
+//! Types and conversions for the VEML3235.
+//!
+//! The VEML3235 is an ambient light sensor with 16-bit ALS and white channels
+//! at the same address as the VEML7700 (0x10) but with its own register map.
+//! It offers integration times from 50 ms to 800 ms, a gain of 1, 2 or 4 and a
+//! digital gain of 1 or 2. It has no thresholds, interrupt or power-saving
+//! mode.
+//!
+//! The driver is available as `Veml3235` in the [`blocking`](crate::blocking)
+//! and [`asynch`](crate::asynch) modules.
+
+#[cfg(feature = "lux_as_f32")]
+use crate::Lux;
+
+/// Integration time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegrationTime {
+    /// 50 ms (default)
+    _50ms,
+    /// 100 ms
+    _100ms,
+    /// 200 ms
+    _200ms,
+    /// 400 ms
+    _400ms,
+    /// 800 ms
+    _800ms,
+}
+
+impl IntegrationTime {
+    /// Return the integration time in milliseconds
+    pub fn as_ms(&self) -> u16 {
+        match self {
+            IntegrationTime::_50ms => 50,
+            IntegrationTime::_100ms => 100,
+            IntegrationTime::_200ms => 200,
+            IntegrationTime::_400ms => 400,
+            IntegrationTime::_800ms => 800,
+        }
+    }
+
+    /// Return the integration time in microseconds
+    pub fn as_us(&self) -> u32 {
+        (self.as_ms() as u32) * 1000
+    }
+}
+
+/// Gain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gain {
+    /// 1 (default)
+    One,
+    /// 2
+    Two,
+    /// 4
+    Four,
+}
+
+/// Digital gain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DigitalGain {
+    /// 1 (default)
+    One,
+    /// 2
+    Two,
+}
+
+pub(crate) const DEVICE_ADDRESS: u8 = 0x10;
+
+pub(crate) struct Register;
+impl Register {
+    pub(crate) const CONF: u8 = 0x00;
+    pub(crate) const WHITE: u8 = 0x04;
+    pub(crate) const ALS: u8 = 0x05;
+}
+
+// Configuration register layout: SD at bit 15, DG at bit 13, gain at bits
+// 12:11, integration time at bits 6:4 and SD0 at bit 0.
+pub(crate) struct BitFlags;
+impl BitFlags {
+    /// Both shutdown bits: SD for the ALS and SD0 for both channels.
+    pub(crate) const SD: u16 = 1 | 1 << 15;
+    pub(crate) const DG: u16 = 1 << 13;
+}
+
+pub(crate) fn encode_integration_time(bits: u16, it: IntegrationTime) -> u16 {
+    let mask = match it {
+        IntegrationTime::_50ms => 0,
+        IntegrationTime::_100ms => 1,
+        IntegrationTime::_200ms => 2,
+        IntegrationTime::_400ms => 3,
+        IntegrationTime::_800ms => 4,
+    };
+    bits & !(0b111 << 4) | mask << 4
+}
+
+pub(crate) fn encode_gain(bits: u16, gain: Gain) -> u16 {
+    let mask = match gain {
+        Gain::One => 0,
+        Gain::Two => 1,
+        Gain::Four => 3,
+    };
+    bits & !(0b11 << 11) | mask << 11
+}
+
+/// Calculate lux value for a raw ALS measurement.
+///
+/// The resolution is 0.00213 lx/count at 800 ms, gain 4 and digital gain 2.
+/// It doubles with each halving of the integration time, of the gain and of
+/// the digital gain.
+#[cfg(feature = "lux_as_f32")]
+pub fn convert_raw_als_to_lux(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    raw_als: u16,
+) -> Lux {
+    Lux(f32::from(raw_als) * get_millilux_raw_conversion_divisor(it, gain, dg) as f32 * 0.00001)
+}
+
+/// Calculate millilux value for a raw ALS measurement.
+///
+/// This is the integer counterpart of `convert_raw_als_to_lux()` and exact
+/// apart from the final truncation.
+#[cfg(feature = "lux_as_u32")]
+pub fn convert_raw_als_to_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    raw_als: u16,
+) -> u32 {
+    let millilux = u64::from(raw_als) * get_millilux_raw_conversion_divisor(it, gain, dg) / 100;
+    millilux as u32
+}
+
+/// Resolution in hundredths of millilux per count.
+#[cfg(any(feature = "lux_as_f32", feature = "lux_as_u32"))]
+const fn get_millilux_raw_conversion_divisor(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+) -> u64 {
+    let gain_factor = match gain {
+        Gain::Four => 1,
+        Gain::Two => 2,
+        Gain::One => 4,
+    };
+    let dg_factor = match dg {
+        DigitalGain::Two => 1,
+        DigitalGain::One => 2,
+    };
+    let it_shift = match it {
+        IntegrationTime::_800ms => 0,
+        IntegrationTime::_400ms => 1,
+        IntegrationTime::_200ms => 2,
+        IntegrationTime::_100ms => 3,
+        IntegrationTime::_50ms => 4,
+    };
+    (213 * gain_factor * dg_factor) << it_shift
+}
@@ -0,0 +1,147 @@
+use super::interface::{read_register, write_register};
+use super::{maybe_async, I2c, SevenBitAddress};
+#[cfg(feature = "lux_as_f32")]
+use crate::veml3235::convert_raw_als_to_lux;
+#[cfg(feature = "lux_as_u32")]
+use crate::veml3235::convert_raw_als_to_millilux;
+use crate::veml3235::{
+    encode_gain, encode_integration_time, BitFlags, DigitalGain, Gain, IntegrationTime, Register,
+    DEVICE_ADDRESS,
+};
+use crate::Error;
+#[cfg(feature = "lux_as_f32")]
+use crate::Lux;
+
+/// VEML3235 device driver.
+///
+/// This offers the configuration and measurement API of the VEML7700 driver
+/// for the features the VEML3235 has. See the [`veml3235`](crate::veml3235)
+/// module.
+#[derive(Debug)]
+pub struct Veml3235<I2C> {
+    /// The concrete I²C device implementation.
+    i2c: I2C,
+    bits: u16,
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+}
+
+impl<I2C> Veml3235<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Create new instance of the VEML3235 device.
+    ///
+    /// This assumes the device is in its power-on state.
+    pub fn new(i2c: I2C) -> Self {
+        Veml3235 {
+            i2c,
+            bits: BitFlags::SD,
+            it: IntegrationTime::_50ms,
+            gain: Gain::One,
+            dg: DigitalGain::One,
+        }
+    }
+
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Enable the device.
+    #[maybe_async]
+    pub async fn enable(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.set_config(self.bits & !BitFlags::SD).await
+    }
+
+    /// Disable the device (shutdown).
+    #[maybe_async]
+    pub async fn disable(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.set_config(self.bits | BitFlags::SD).await
+    }
+
+    /// Set the integration time.
+    #[maybe_async]
+    pub async fn set_integration_time(
+        &mut self,
+        it: IntegrationTime,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.set_config(encode_integration_time(self.bits, it))
+            .await?;
+        self.it = it;
+        Ok(())
+    }
+
+    /// Set the gain.
+    #[maybe_async]
+    pub async fn set_gain(&mut self, gain: Gain) -> Result<(), Error<I2C::Error>> {
+        self.set_config(encode_gain(self.bits, gain)).await?;
+        self.gain = gain;
+        Ok(())
+    }
+
+    /// Set the digital gain.
+    #[maybe_async]
+    pub async fn set_digital_gain(&mut self, dg: DigitalGain) -> Result<(), Error<I2C::Error>> {
+        let bits = match dg {
+            DigitalGain::One => self.bits & !BitFlags::DG,
+            DigitalGain::Two => self.bits | BitFlags::DG,
+        };
+        self.set_config(bits).await?;
+        self.dg = dg;
+        Ok(())
+    }
+
+    /// Read ALS high resolution output data in raw format
+    #[maybe_async]
+    pub async fn read_raw(&mut self) -> Result<u16, Error<I2C::Error>> {
+        read_register(&mut self.i2c, DEVICE_ADDRESS, Register::ALS).await
+    }
+
+    /// Read ALS high resolution output data converted to lux
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_lux(&mut self) -> Result<Lux, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.convert_raw_als_to_lux(raw))
+    }
+
+    /// Calculate lux value for a raw ALS measurement with the current
+    /// settings.
+    #[cfg(feature = "lux_as_f32")]
+    pub fn convert_raw_als_to_lux(&self, raw_als: u16) -> Lux {
+        convert_raw_als_to_lux(self.it, self.gain, self.dg, raw_als)
+    }
+
+    /// Read ALS high resolution output data converted to millilux
+    ///
+    /// This is the integer counterpart of `read_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn read_millilux(&mut self) -> Result<u32, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.convert_raw_als_to_millilux(raw))
+    }
+
+    /// Calculate millilux value for a raw ALS measurement with the current
+    /// settings.
+    #[cfg(feature = "lux_as_u32")]
+    pub fn convert_raw_als_to_millilux(&self, raw_als: u16) -> u32 {
+        convert_raw_als_to_millilux(self.it, self.gain, self.dg, raw_als)
+    }
+
+    /// Read white channel measurement
+    #[maybe_async]
+    pub async fn read_white(&mut self) -> Result<u16, Error<I2C::Error>> {
+        read_register(&mut self.i2c, DEVICE_ADDRESS, Register::WHITE).await
+    }
+
+    #[maybe_async]
+    async fn set_config(&mut self, bits: u16) -> Result<(), Error<I2C::Error>> {
+        write_register(&mut self.i2c, DEVICE_ADDRESS, Register::CONF, bits).await?;
+        self.bits = bits;
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+//! Types and conversions for the VEML6035.
+//!
+//! The VEML6035 is a low-power ambient light sensor with the same ALS, WHITE,
+//! threshold, power-saving mode and interrupt status registers as the
+//! VEML7700 but a different ALS_CONF layout. Instead of a single gain it has
+//! an analog gain, a digital gain and a sensitivity setting, and the white
+//! channel has to be enabled separately. The integration times and the fault
+//! count are the same as for the VEML7700.
+//!
+//! The driver is available as `Veml6035` in the [`blocking`](crate::blocking)
+//! and [`asynch`](crate::asynch) modules.
+//!
+//! Datasheet: [VEML6035](https://www.vishay.com/docs/84889/veml6035.pdf)
+
+use crate::millilux::integration_time_shift;
+use crate::IntegrationTime;
+#[cfg(feature = "lux_as_f32")]
+use crate::Lux;
+
+/// Gain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gain {
+    /// 1 (default)
+    One,
+    /// 2
+    Two,
+}
+
+/// Digital gain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DigitalGain {
+    /// 1 (default)
+    One,
+    /// 2
+    Two,
+}
+
+/// Sensitivity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sensitivity {
+    /// High sensitivity (default)
+    High,
+    /// Low sensitivity: 1/8 of the high sensitivity
+    Low,
+}
+
+pub(crate) const DEVICE_ADDRESS: u8 = 0x29;
+
+pub(crate) struct BitFlags;
+impl BitFlags {
+    pub(crate) const WHITE_EN: u16 = 1 << 2;
+    pub(crate) const GAIN: u16 = 1 << 10;
+    pub(crate) const DG: u16 = 1 << 11;
+    pub(crate) const SENS: u16 = 1 << 12;
+}
+
+/// Calculate lux value for a raw ALS measurement.
+///
+/// The resolution is 0.0004 lx/count at 800 ms, gain 2, digital gain 2 and
+/// high sensitivity. It doubles with each halving of the integration time and
+/// of either gain, and low sensitivity multiplies it by 8. No compensation
+/// formula is necessary.
+#[cfg(feature = "lux_as_f32")]
+pub fn convert_raw_als_to_lux(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+    raw_als: u16,
+) -> Lux {
+    Lux(f32::from(raw_als) * get_lux_raw_conversion_factor(it, gain, dg, sens))
+}
+
+/// Calculate millilux value for a raw ALS measurement.
+///
+/// This is the integer counterpart of `convert_raw_als_to_lux()` and exact
+/// apart from the final truncation.
+#[cfg(feature = "lux_as_u32")]
+pub fn convert_raw_als_to_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+    raw_als: u16,
+) -> u32 {
+    let millilux =
+        u64::from(raw_als) * get_millilux_raw_conversion_divisor(it, gain, dg, sens) / 10;
+    millilux as u32
+}
+
+/// Calculate raw value for threshold.
+///
+/// Values outside of the range of the ALS register saturate to 0 or
+/// `u16::MAX`. The driver methods setting thresholds in lux return
+/// `Error::InvalidThreshold` for these instead.
+#[cfg(feature = "lux_as_f32")]
+pub fn calculate_raw_threshold_value(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+    lux: Lux,
+) -> u16 {
+    (lux.0 / get_lux_raw_conversion_factor(it, gain, dg, sens)) as u16
+}
+
+/// Like `calculate_raw_threshold_value()` but return `None` for values
+/// that cannot be represented in the ALS register.
+#[cfg(feature = "lux_as_f32")]
+pub(crate) fn checked_raw_threshold_value(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+    lux: Lux,
+) -> Option<u16> {
+    let raw = lux.0 / get_lux_raw_conversion_factor(it, gain, dg, sens);
+    // This also rejects NaN.
+    if (0.0..65536.0).contains(&raw) {
+        Some(raw as u16)
+    } else {
+        None
+    }
+}
+
+/// Calculate raw value for threshold in millilux.
+///
+/// This is the integer counterpart of `calculate_raw_threshold_value()` and a
+/// `const fn` not needing any feature, so threshold tables can be calculated
+/// at compile time. Values above the range of the ALS register saturate to
+/// `u16::MAX`.
+pub const fn calculate_raw_threshold_value_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+    millilux: u32,
+) -> u16 {
+    match checked_raw_threshold_value_millilux(it, gain, dg, sens, millilux) {
+        Some(raw) => raw,
+        None => u16::MAX,
+    }
+}
+
+/// Like `calculate_raw_threshold_value_millilux()` but return `None` for
+/// values above the range of the ALS register.
+pub(crate) const fn checked_raw_threshold_value_millilux(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+    millilux: u32,
+) -> Option<u16> {
+    let raw = millilux as u64 * 10 / get_millilux_raw_conversion_divisor(it, gain, dg, sens);
+    if raw > u16::MAX as u64 {
+        None
+    } else {
+        Some(raw as u16)
+    }
+}
+
+#[cfg(feature = "lux_as_f32")]
+fn get_lux_raw_conversion_factor(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+) -> f32 {
+    get_millilux_raw_conversion_divisor(it, gain, dg, sens) as f32 * 0.0001
+}
+
+/// Resolution in tenths of millilux per count.
+const fn get_millilux_raw_conversion_divisor(
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+) -> u64 {
+    let gain_factor = match gain {
+        Gain::Two => 1,
+        Gain::One => 2,
+    };
+    let dg_factor = match dg {
+        DigitalGain::Two => 1,
+        DigitalGain::One => 2,
+    };
+    let sens_factor = match sens {
+        Sensitivity::High => 1,
+        Sensitivity::Low => 8,
+    };
+    (4 * gain_factor * dg_factor * sens_factor) << integration_time_shift(it)
+}
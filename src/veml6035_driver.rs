@@ -0,0 +1,409 @@
+use super::interface::{read_register, write_register};
+use super::{maybe_async, I2c, SevenBitAddress};
+use crate::device_impl::{encode_power_saving_mode, BitFlags, Register};
+#[cfg(feature = "lux_as_f32")]
+use crate::veml6035::convert_raw_als_to_lux;
+#[cfg(feature = "lux_as_u32")]
+use crate::veml6035::convert_raw_als_to_millilux;
+use crate::veml6035::{self, DigitalGain, Gain, Sensitivity, DEVICE_ADDRESS};
+#[cfg(feature = "lux_as_f32")]
+use crate::veml6035::{calculate_raw_threshold_value, checked_raw_threshold_value};
+#[cfg(feature = "lux_as_u32")]
+use crate::veml6035::{
+    calculate_raw_threshold_value_millilux, checked_raw_threshold_value_millilux,
+};
+#[cfg(feature = "lux_as_f32")]
+use crate::Lux;
+use crate::{Config, Error, FaultCount, IntegrationTime, InterruptStatus, PowerSavingMode};
+
+/// VEML6035 device driver.
+///
+/// This offers the same configuration, threshold and interrupt API as the
+/// VEML7700 driver, with the gain replaced by the VEML6035 gain, digital gain
+/// and sensitivity. See the [`veml6035`](crate::veml6035) module.
+#[derive(Debug)]
+pub struct Veml6035<I2C> {
+    /// The concrete I²C device implementation.
+    i2c: I2C,
+    config: Config,
+    it: IntegrationTime,
+    gain: Gain,
+    dg: DigitalGain,
+    sens: Sensitivity,
+}
+
+impl<I2C> Veml6035<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Create new instance of the VEML6035 device.
+    ///
+    /// This assumes the device is in its power-on state.
+    pub fn new(i2c: I2C) -> Self {
+        Veml6035 {
+            i2c,
+            config: Config {
+                bits: BitFlags::ALS_SD,
+            },
+            it: IntegrationTime::_100ms,
+            gain: Gain::One,
+            dg: DigitalGain::One,
+            sens: Sensitivity::High,
+        }
+    }
+
+    /// Destroy driver instance, return I²C bus instance.
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Enable the device.
+    ///
+    /// Note that when activating the sensor a wait time of 4 ms should be
+    /// observed before the first measurement is picked up.
+    #[maybe_async]
+    pub async fn enable(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_low(BitFlags::ALS_SD);
+        self.set_config(config).await
+    }
+
+    /// Disable the device (shutdown).
+    #[maybe_async]
+    pub async fn disable(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_high(BitFlags::ALS_SD);
+        self.set_config(config).await
+    }
+
+    /// Set the integration time.
+    #[maybe_async]
+    pub async fn set_integration_time(
+        &mut self,
+        it: IntegrationTime,
+    ) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_integration_time(it);
+        self.set_config(config).await?;
+        self.it = it;
+        Ok(())
+    }
+
+    /// Set the gain.
+    #[maybe_async]
+    pub async fn set_gain(&mut self, gain: Gain) -> Result<(), Error<I2C::Error>> {
+        let config = self
+            .config
+            .with_flag(veml6035::BitFlags::GAIN, gain == Gain::Two);
+        self.set_config(config).await?;
+        self.gain = gain;
+        Ok(())
+    }
+
+    /// Set the digital gain.
+    #[maybe_async]
+    pub async fn set_digital_gain(&mut self, dg: DigitalGain) -> Result<(), Error<I2C::Error>> {
+        let config = self
+            .config
+            .with_flag(veml6035::BitFlags::DG, dg == DigitalGain::Two);
+        self.set_config(config).await?;
+        self.dg = dg;
+        Ok(())
+    }
+
+    /// Set the sensitivity.
+    #[maybe_async]
+    pub async fn set_sensitivity(&mut self, sens: Sensitivity) -> Result<(), Error<I2C::Error>> {
+        let config = self
+            .config
+            .with_flag(veml6035::BitFlags::SENS, sens == Sensitivity::Low);
+        self.set_config(config).await?;
+        self.sens = sens;
+        Ok(())
+    }
+
+    /// Set the number of times a threshold crossing must happen consecutively
+    /// to trigger an interrupt.
+    #[maybe_async]
+    pub async fn set_fault_count(&mut self, fc: FaultCount) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_fault_count(fc);
+        self.set_config(config).await
+    }
+
+    /// Enable interrupt generation.
+    #[maybe_async]
+    pub async fn enable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_high(BitFlags::ALS_INT_EN);
+        self.set_config(config).await
+    }
+
+    /// Disable interrupt generation.
+    #[maybe_async]
+    pub async fn disable_interrupts(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_low(BitFlags::ALS_INT_EN);
+        self.set_config(config).await
+    }
+
+    /// Enable the white channel.
+    ///
+    /// The white channel is disabled after power-on.
+    #[maybe_async]
+    pub async fn enable_white_channel(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_high(veml6035::BitFlags::WHITE_EN);
+        self.set_config(config).await
+    }
+
+    /// Disable the white channel.
+    #[maybe_async]
+    pub async fn disable_white_channel(&mut self) -> Result<(), Error<I2C::Error>> {
+        let config = self.config.with_low(veml6035::BitFlags::WHITE_EN);
+        self.set_config(config).await
+    }
+
+    /// Enable the power-saving mode
+    ///
+    /// Note that the wait times of the VEML6035 differ from the ones of the
+    /// VEML7700. See the datasheet.
+    #[maybe_async]
+    pub async fn enable_power_saving(
+        &mut self,
+        psm: PowerSavingMode,
+    ) -> Result<(), Error<I2C::Error>> {
+        let bits = encode_power_saving_mode(Some(psm));
+        self.write_register(Register::PSM, bits).await
+    }
+
+    /// Disable the power-saving mode
+    #[maybe_async]
+    pub async fn disable_power_saving(&mut self) -> Result<(), Error<I2C::Error>> {
+        let bits = encode_power_saving_mode(None);
+        self.write_register(Register::PSM, bits).await
+    }
+
+    #[maybe_async]
+    async fn set_config(&mut self, config: Config) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::ALS_CONF, config.bits).await?;
+        self.config = config;
+        Ok(())
+    }
+}
+
+impl<I2C> Veml6035<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Set the ALS high threshold in raw format
+    #[maybe_async]
+    pub async fn set_high_threshold_raw(
+        &mut self,
+        threshold: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::ALS_WH, threshold).await
+    }
+
+    /// Set the ALS low threshold in raw format
+    #[maybe_async]
+    pub async fn set_low_threshold_raw(&mut self, threshold: u16) -> Result<(), Error<I2C::Error>> {
+        self.write_register(Register::ALS_WL, threshold).await
+    }
+
+    /// Set the ALS low and high thresholds in raw format.
+    ///
+    /// Returns `Error::InvalidThreshold` if `low` is above `high`.
+    #[maybe_async]
+    pub async fn set_thresholds_raw(
+        &mut self,
+        low: u16,
+        high: u16,
+    ) -> Result<(), Error<I2C::Error>> {
+        if low > high {
+            return Err(Error::InvalidThreshold);
+        }
+        self.set_low_threshold_raw(low).await?;
+        self.set_high_threshold_raw(high).await
+    }
+
+    /// Set the ALS high threshold in lux.
+    ///
+    /// Returns `Error::InvalidThreshold` if the value cannot be represented
+    /// with the current settings.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_high_threshold_lux(&mut self, lux: Lux) -> Result<(), Error<I2C::Error>> {
+        let raw = self.checked_raw_threshold_value(lux)?;
+        self.set_high_threshold_raw(raw).await
+    }
+
+    /// Set the ALS low threshold in lux.
+    ///
+    /// Returns `Error::InvalidThreshold` if the value cannot be represented
+    /// with the current settings.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_low_threshold_lux(&mut self, lux: Lux) -> Result<(), Error<I2C::Error>> {
+        let raw = self.checked_raw_threshold_value(lux)?;
+        self.set_low_threshold_raw(raw).await
+    }
+
+    /// Set the ALS low and high thresholds in lux.
+    ///
+    /// Returns `Error::InvalidThreshold` if `low` is above `high` or either
+    /// value cannot be represented with the current settings.
+    /// Nothing is written in that case.
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn set_thresholds_lux(
+        &mut self,
+        low: Lux,
+        high: Lux,
+    ) -> Result<(), Error<I2C::Error>> {
+        let low = self.checked_raw_threshold_value(low)?;
+        let high = self.checked_raw_threshold_value(high)?;
+        self.set_thresholds_raw(low, high).await
+    }
+
+    /// Calculate raw value for threshold with the current settings.
+    #[cfg(feature = "lux_as_f32")]
+    pub fn calculate_raw_threshold_value(&self, lux: Lux) -> u16 {
+        calculate_raw_threshold_value(self.it, self.gain, self.dg, self.sens, lux)
+    }
+
+    #[cfg(feature = "lux_as_f32")]
+    fn checked_raw_threshold_value(&self, lux: Lux) -> Result<u16, Error<I2C::Error>> {
+        checked_raw_threshold_value(self.it, self.gain, self.dg, self.sens, lux)
+            .ok_or(Error::InvalidThreshold)
+    }
+
+    /// Set the ALS high threshold in millilux.
+    ///
+    /// This is the integer counterpart of `set_high_threshold_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_high_threshold_millilux(
+        &mut self,
+        millilux: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let raw = self.checked_raw_threshold_value_millilux(millilux)?;
+        self.set_high_threshold_raw(raw).await
+    }
+
+    /// Set the ALS low threshold in millilux.
+    ///
+    /// This is the integer counterpart of `set_low_threshold_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_low_threshold_millilux(
+        &mut self,
+        millilux: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let raw = self.checked_raw_threshold_value_millilux(millilux)?;
+        self.set_low_threshold_raw(raw).await
+    }
+
+    /// Set the ALS low and high thresholds in millilux.
+    ///
+    /// This is the integer counterpart of `set_thresholds_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn set_thresholds_millilux(
+        &mut self,
+        low: u32,
+        high: u32,
+    ) -> Result<(), Error<I2C::Error>> {
+        let low = self.checked_raw_threshold_value_millilux(low)?;
+        let high = self.checked_raw_threshold_value_millilux(high)?;
+        self.set_thresholds_raw(low, high).await
+    }
+
+    /// Calculate raw value for a threshold in millilux with the current
+    /// settings.
+    #[cfg(feature = "lux_as_u32")]
+    pub fn calculate_raw_threshold_value_millilux(&self, millilux: u32) -> u16 {
+        calculate_raw_threshold_value_millilux(self.it, self.gain, self.dg, self.sens, millilux)
+    }
+
+    #[cfg(feature = "lux_as_u32")]
+    fn checked_raw_threshold_value_millilux(
+        &self,
+        millilux: u32,
+    ) -> Result<u16, Error<I2C::Error>> {
+        checked_raw_threshold_value_millilux(self.it, self.gain, self.dg, self.sens, millilux)
+            .ok_or(Error::InvalidThreshold)
+    }
+
+    #[maybe_async]
+    async fn write_register(&mut self, register: u8, value: u16) -> Result<(), Error<I2C::Error>> {
+        write_register(&mut self.i2c, DEVICE_ADDRESS, register, value).await
+    }
+}
+
+impl<I2C> Veml6035<I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+    I2C::Error: Into<Error<I2C::Error>>,
+{
+    /// Read whether an interrupt has occurred.
+    ///
+    /// Note that the interrupt status is updated at the same rate as the
+    /// measurements. Reading it clears the flags and releases the INT pin.
+    /// They are set again once the fault count of consecutive measurements
+    /// exceeds a threshold again.
+    #[maybe_async]
+    pub async fn read_interrupt_status(&mut self) -> Result<InterruptStatus, Error<I2C::Error>> {
+        let data = self.read_register(Register::ALS_INT).await?;
+        Ok(InterruptStatus {
+            was_too_low: (data & BitFlags::INT_TH_LOW) != 0,
+            was_too_high: (data & BitFlags::INT_TH_HIGH) != 0,
+        })
+    }
+
+    /// Read ALS high resolution output data in raw format
+    #[maybe_async]
+    pub async fn read_raw(&mut self) -> Result<u16, Error<I2C::Error>> {
+        self.read_register(Register::ALS).await
+    }
+
+    /// Read ALS high resolution output data converted to lux
+    #[cfg(feature = "lux_as_f32")]
+    #[maybe_async]
+    pub async fn read_lux(&mut self) -> Result<Lux, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.convert_raw_als_to_lux(raw))
+    }
+
+    /// Calculate lux value for a raw ALS measurement with the current
+    /// settings.
+    #[cfg(feature = "lux_as_f32")]
+    pub fn convert_raw_als_to_lux(&self, raw_als: u16) -> Lux {
+        convert_raw_als_to_lux(self.it, self.gain, self.dg, self.sens, raw_als)
+    }
+
+    /// Read ALS high resolution output data converted to millilux
+    ///
+    /// This is the integer counterpart of `read_lux()`.
+    #[cfg(feature = "lux_as_u32")]
+    #[maybe_async]
+    pub async fn read_millilux(&mut self) -> Result<u32, Error<I2C::Error>> {
+        let raw = self.read_raw().await?;
+        Ok(self.convert_raw_als_to_millilux(raw))
+    }
+
+    /// Calculate millilux value for a raw ALS measurement with the current
+    /// settings.
+    #[cfg(feature = "lux_as_u32")]
+    pub fn convert_raw_als_to_millilux(&self, raw_als: u16) -> u32 {
+        convert_raw_als_to_millilux(self.it, self.gain, self.dg, self.sens, raw_als)
+    }
+
+    /// Read white channel measurement
+    ///
+    /// The white channel must be enabled with `enable_white_channel()`.
+    #[maybe_async]
+    pub async fn read_white(&mut self) -> Result<u16, Error<I2C::Error>> {
+        self.read_register(Register::WHITE).await
+    }
+
+    #[maybe_async]
+    async fn read_register(&mut self, register: u8) -> Result<u16, Error<I2C::Error>> {
+        read_register(&mut self.i2c, DEVICE_ADDRESS, register).await
+    }
+}